@@ -0,0 +1,271 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::runner::{solve_catching, Answers, Params};
+
+const ANSWERS_EXTENSION: &str = "answers";
+const PARAMS_EXTENSION: &str = "params";
+
+#[derive(Debug, PartialEq)]
+pub enum Check {
+    Unchecked,
+    Correct,
+    Wrong(String),
+}
+
+pub struct BatchRow {
+    pub name: String,
+    pub result: Result<Answers, String>,
+    pub part1_check: Check,
+    pub part2_check: Check,
+    pub elapsed: Duration,
+}
+
+/// Reads expected answers in the same shape the runner prints them, so the
+/// output of `cargo run -- --day N` can be saved as-is next to an input:
+///
+/// ```text
+/// Part 1: 11
+/// Part 2: 31
+/// ```
+pub fn parse_answers(contents: &str) -> (Option<String>, Option<String>) {
+    contents.lines().fold((None, None), |(part1, part2), line| {
+        if let Some(value) = line.strip_prefix("Part 1:") {
+            (Some(value.trim().to_owned()), part2)
+        } else if let Some(value) = line.strip_prefix("Part 2:") {
+            (part1, Some(value.trim().to_owned()))
+        } else {
+            (part1, part2)
+        }
+    })
+}
+
+/// Reads puzzle parameters as `name=value` lines, e.g. `width=11`. A missing
+/// file leaves every parameter at its default.
+pub fn read_params(path: &Path) -> Result<Params, String> {
+    let mut params = Params::default();
+    let Ok(contents) = fs::read_to_string(path) else {
+        return Ok(params);
+    };
+    for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+        let (name, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("expected name=value, got {}", line))?;
+        params.set(name.trim(), value.trim())?;
+    }
+    Ok(params)
+}
+
+fn check(expected: &Option<String>, actual: Option<&String>) -> Check {
+    match (expected, actual) {
+        (None, _) | (_, None) => Check::Unchecked,
+        (Some(e), Some(a)) if e == a => Check::Correct,
+        (Some(e), Some(_)) => Check::Wrong(e.clone()),
+    }
+}
+
+/// Every file in `dir` apart from the answers and parameters kept alongside
+/// the inputs
+fn input_files(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut files = fs::read_dir(dir)
+        .map_err(|e| format!("Error reading {}: {}", dir.display(), e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .is_none_or(|ext| ext != ANSWERS_EXTENSION && ext != PARAMS_EXTENSION)
+        })
+        .collect::<Vec<PathBuf>>();
+    files.sort();
+    Ok(files)
+}
+
+fn run_file(day: i16, path: &Path) -> BatchRow {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let (expected1, expected2) = fs::read_to_string(path.with_extension(ANSWERS_EXTENSION))
+        .map(|contents| parse_answers(&contents))
+        .unwrap_or((None, None));
+
+    let start = Instant::now();
+    let result = read_params(&path.with_extension(PARAMS_EXTENSION))
+        .map_err(|e| format!("Invalid params: {}", e))
+        .and_then(|params| {
+            fs::read_to_string(path)
+                .map_err(|e| format!("Error reading input: {}", e))
+                .and_then(|input| solve_catching(day, input, &params))
+        });
+    let elapsed = start.elapsed();
+
+    let part1_check = check(&expected1, result.as_ref().ok().map(|a| &a.part1));
+    let part2_check = check(&expected2, result.as_ref().ok().map(|a| &a.part2));
+
+    BatchRow {
+        name,
+        result,
+        part1_check,
+        part2_check,
+        elapsed,
+    }
+}
+
+pub fn run_batch(day: i16, dir: &Path) -> Result<Vec<BatchRow>, String> {
+    Ok(input_files(dir)?
+        .iter()
+        .map(|path| run_file(day, path))
+        .collect())
+}
+
+fn format_cell(answer: &str, check: &Check) -> String {
+    match check {
+        Check::Unchecked => answer.to_owned(),
+        Check::Correct => format!("{} ✓", answer),
+        Check::Wrong(expected) => format!("{} ✗ (expected {})", answer, expected),
+    }
+}
+
+pub fn format_matrix(rows: &[BatchRow]) -> String {
+    let header = vec![
+        "Input".to_owned(),
+        "Part 1".to_owned(),
+        "Part 2".to_owned(),
+        "Time".to_owned(),
+    ];
+    let table = rows
+        .iter()
+        .map(|row| {
+            let (part1, part2) = match &row.result {
+                Ok(answers) => (
+                    format_cell(&answers.part1, &row.part1_check),
+                    format_cell(&answers.part2, &row.part2_check),
+                ),
+                Err(e) => (format!("FAILED: {}", e), "-".to_owned()),
+            };
+            vec![
+                row.name.clone(),
+                part1,
+                part2,
+                format!("{:.1?}", row.elapsed),
+            ]
+        })
+        .collect::<Vec<Vec<String>>>();
+
+    let widths = (0..header.len())
+        .map(|c| {
+            table
+                .iter()
+                .chain(std::iter::once(&header))
+                .map(|r| r[c].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect::<Vec<usize>>();
+
+    let format_row = |cells: &Vec<String>| {
+        cells
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<String>>()
+            .join(" | ")
+            .trim_end()
+            .to_owned()
+    };
+
+    let separator = widths
+        .iter()
+        .map(|w| "-".repeat(*w))
+        .collect::<Vec<String>>()
+        .join("-+-");
+
+    let failures = rows
+        .iter()
+        .filter(|row| {
+            row.result.is_err()
+                || matches!(row.part1_check, Check::Wrong(_))
+                || matches!(row.part2_check, Check::Wrong(_))
+        })
+        .count();
+
+    std::iter::once(format_row(&header))
+        .chain(std::iter::once(separator))
+        .chain(table.iter().map(format_row))
+        .chain(std::iter::once(format!(
+            "{} inputs, {} failed",
+            rows.len(),
+            failures
+        )))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_answers_file() {
+        let contents = r"Day 1 result:
+Part 1: 11
+Part 2: 31
+";
+        assert_eq!(
+            parse_answers(contents),
+            (Some("11".to_owned()), Some("31".to_owned()))
+        );
+        assert_eq!(parse_answers("Part 2: x"), (None, Some("x".to_owned())));
+    }
+
+    #[test]
+    fn runs_directory_of_inputs() {
+        let dir = std::env::temp_dir().join(format!("aoc-batch-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let example = "3   4\n4   3\n2   5\n1   3\n3   9\n3   3\n";
+        fs::write(dir.join("alice.txt"), example).unwrap();
+        fs::write(dir.join("alice.answers"), "Part 1: 11\nPart 2: 30\n").unwrap();
+        fs::write(dir.join("bob.txt"), "garbage").unwrap();
+        fs::write(dir.join("bob.params"), "width=11\n").unwrap();
+        fs::write(dir.join("carol.txt"), example).unwrap();
+        fs::write(dir.join("carol.params"), "width=eleven\n").unwrap();
+
+        let rows = run_batch(1, &dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].name, "alice.txt");
+        assert_eq!(rows[0].part1_check, Check::Correct);
+        assert_eq!(rows[0].part2_check, Check::Wrong("30".to_owned()));
+        assert_eq!(rows[1].name, "bob.txt");
+        assert!(rows[1].result.is_err());
+        assert_eq!(rows[2].name, "carol.txt");
+        assert!(rows[2]
+            .result
+            .as_ref()
+            .is_err_and(|e| e.starts_with("Invalid params: Invalid value eleven for width")));
+
+        let matrix = format_matrix(&rows);
+        assert!(matrix.ends_with("3 inputs, 3 failed"));
+    }
+
+    #[test]
+    fn applies_params_next_to_inputs() {
+        let dir = std::env::temp_dir().join(format!("aoc-batch-params-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let example = "5,4\n4,2\n4,5\n3,0\n2,1\n6,3\n2,4\n1,5\n0,6\n3,3\n2,6\n5,1\n1,2\n5,5\n2,5\n6,5\n1,4\n0,4\n6,4\n1,1\n6,1\n1,0\n0,5\n1,6\n2,0\n";
+        fs::write(dir.join("example.txt"), example).unwrap();
+        fs::write(dir.join("example.params"), "grid_size=7\nfalls=12\n").unwrap();
+        fs::write(dir.join("example.answers"), "Part 1: 22\nPart 2: 6,1\n").unwrap();
+
+        let rows = run_batch(18, &dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].part1_check, Check::Correct);
+        assert_eq!(rows[0].part2_check, Check::Correct);
+    }
+}
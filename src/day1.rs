@@ -1,65 +1,457 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::iter::Peekable;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+use crate::parse;
+
 pub struct Day1 {
-    pub diff_sum: i32,
-    pub similarity_score: i32,
-}
-
-fn parse_input(input: String) -> Vec<(i32, i32)> {
-    input
-        .lines()
-        .map(|line| {
-            let (l, r) = line.split_once(" ").unwrap();
-            (
-                str::parse::<i32>(l).unwrap(),
-                str::parse::<i32>(r.trim_start()).unwrap(),
-            )
-        })
-        .collect::<Vec<(i32, i32)>>()
+    pub diff_sum: i64,
+    pub similarity_score: i64,
+}
+
+fn parse_pair(line: &str) -> Result<(i64, i64), String> {
+    let (l, r) = parse::split(line, " ")?;
+    Ok((parse::number(l)?, parse::number(r.trim_start())?))
+}
+
+/// Calls `f` with each pair, read a line at a time
+fn for_each_pair(
+    mut reader: impl BufRead,
+    mut f: impl FnMut(i64, i64) -> Result<(), String>,
+) -> Result<(), String> {
+    let mut line = String::new();
+    for i in 0.. {
+        line.clear();
+        let read = reader
+            .read_line(&mut line)
+            .map_err(|e| format!("Error reading input: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        let (l, r) = parse::on_line(i, parse_pair(line.trim_end_matches(['\n', '\r'])))?;
+        f(l, r)?;
+    }
+    Ok(())
 }
 
-fn diff_sum(input: Vec<(i32, i32)>) -> i32 {
-    let mut ordered_l: Vec<i32> = vec![];
-    let mut ordered_r: Vec<i32> = vec![];
+/// The left and right lists, read a line at a time so that the input never
+/// has to be held in memory alongside them
+pub fn read_lists(reader: impl BufRead) -> Result<(Vec<i64>, Vec<i64>), String> {
+    let mut left = vec![];
+    let mut right = vec![];
+    for_each_pair(reader, |l, r| {
+        left.push(l);
+        right.push(r);
+        Ok(())
+    })?;
+    Ok((left, right))
+}
+
+pub(crate) fn parse_input(input: &str) -> Result<(Vec<i64>, Vec<i64>), String> {
+    read_lists(input.as_bytes())
+}
+
+fn diff_sum(left: &mut [i64], right: &mut [i64]) -> Result<i64, String> {
+    left.sort_unstable();
+    right.sort_unstable();
 
-    input.iter().for_each(|(l, r)| {
-        ordered_l.push(l.to_owned());
-        ordered_r.push(r.to_owned());
-    });
+    left.iter()
+        .zip(right.iter())
+        .try_fold(0_i64, |sum, (l, r)| {
+            i64::try_from(l.abs_diff(*r))
+                .ok()
+                .and_then(|diff| sum.checked_add(diff))
+        })
+        .ok_or_else(|| "Total distance is too large".to_owned())
+}
+
+fn similarity_score(left: &[i64], right: &[i64]) -> Result<i64, String> {
+    let mut counts: HashMap<i64, i64> = HashMap::new();
+    right
+        .iter()
+        .for_each(|r| *counts.entry(*r).or_insert(0) += 1);
 
-    ordered_l.sort();
-    ordered_r.sort();
+    left.iter()
+        .try_fold(0_i64, |sum, l| {
+            l.checked_mul(counts.get(l).copied().unwrap_or(0))
+                .and_then(|score| sum.checked_add(score))
+        })
+        .ok_or_else(|| "Similarity score is too large".to_owned())
+}
 
-    ordered_l.iter().enumerate().fold(0, |sum, (i, l)| {
-        let diff = l - ordered_r[i];
-        sum + (if diff > 0 { diff } else { -1 * diff })
+/// Solves a list of any length straight from a reader
+pub fn day1_from_reader(reader: impl BufRead) -> Result<Day1, String> {
+    let (mut left, mut right) = read_lists(reader)?;
+    Ok(Day1 {
+        similarity_score: similarity_score(&left, &right)?,
+        diff_sum: diff_sum(&mut left, &mut right)?,
     })
 }
 
-fn similarity_score(input: Vec<(i32, i32)>) -> i32 {
-    let mut ordered_r: Vec<i32> = vec![];
-    input.iter().for_each(|(_l, r)| {
-        ordered_r.push(r.to_owned());
-    });
-    ordered_r.sort();
+pub fn day1(input: String) -> Result<Day1, String> {
+    day1_from_reader(input.as_bytes())
+}
+
+/// Sorting the lists on disk, for lists too large for memory
+#[derive(Clone, Debug)]
+pub struct ExternalSort {
+    /// Values from each list sorted in memory at a time
+    pub run_length: usize,
+    /// Where the sorted runs are written while solving
+    pub temp_dir: PathBuf,
+}
+
+impl Default for ExternalSort {
+    fn default() -> Self {
+        ExternalSort {
+            run_length: 1_000_000,
+            temp_dir: std::env::temp_dir(),
+        }
+    }
+}
+
+static RUNS_DIRS: AtomicUsize = AtomicUsize::new(0);
+
+/// Sorted runs of one list's values, removed when dropped
+struct Runs {
+    dir: PathBuf,
+    files: Vec<PathBuf>,
+}
+
+impl Runs {
+    fn new(config: &ExternalSort, list: &str) -> Result<Runs, String> {
+        let dir = config.temp_dir.join(format!(
+            "day1-{}-{}-{}",
+            list,
+            std::process::id(),
+            RUNS_DIRS.fetch_add(1, AtomicOrdering::Relaxed)
+        ));
+        fs::create_dir_all(&dir).map_err(|e| format!("Error creating {}: {}", dir.display(), e))?;
+        Ok(Runs { dir, files: vec![] })
+    }
+
+    /// Sorts `values` and writes them out as the next run, leaving `values`
+    /// empty
+    fn write(&mut self, values: &mut Vec<i64>) -> Result<(), String> {
+        if values.is_empty() {
+            return Ok(());
+        }
+        values.sort_unstable();
+        let path = self.dir.join(format!("{}.run", self.files.len()));
+        let error = |e: std::io::Error| format!("Error writing {}: {}", path.display(), e);
+        let mut writer = BufWriter::new(File::create(&path).map_err(error)?);
+        for value in values.drain(..) {
+            writer.write_all(&value.to_le_bytes()).map_err(error)?;
+        }
+        writer.flush().map_err(error)?;
+        self.files.push(path);
+        Ok(())
+    }
 
-    let right_count = |value: &i32| {
-        ordered_r
+    /// Every value in the runs, in order
+    fn merge(&self) -> Result<Merge, String> {
+        let mut runs = self
+            .files
             .iter()
-            .skip_while(|r| r.to_owned() < value)
-            .take_while(|r| r.to_owned() == value)
-            .count()
+            .map(|path| {
+                File::open(path)
+                    .map(|file| (path.clone(), BufReader::new(file)))
+                    .map_err(|e| format!("Error reading {}: {}", path.display(), e))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let mut heap = BinaryHeap::new();
+        for (i, run) in runs.iter_mut().enumerate() {
+            if let Some(value) = read_value(run)? {
+                heap.push(Reverse((value, i)));
+            }
+        }
+        Ok(Merge { runs, heap })
+    }
+}
+
+impl Drop for Runs {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn read_value((path, reader): &mut (PathBuf, BufReader<File>)) -> Result<Option<i64>, String> {
+    let mut bytes = [0; 8];
+    match reader.read_exact(&mut bytes) {
+        Ok(()) => Ok(Some(i64::from_le_bytes(bytes))),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(None),
+        Err(e) => Err(format!("Error reading {}: {}", path.display(), e)),
+    }
+}
+
+/// A k-way merge of sorted runs, keeping the smallest unread value of each
+/// run in a heap
+struct Merge {
+    runs: Vec<(PathBuf, BufReader<File>)>,
+    heap: BinaryHeap<Reverse<(i64, usize)>>,
+}
+
+impl Iterator for Merge {
+    type Item = Result<i64, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse((value, i)) = self.heap.pop()?;
+        match read_value(&mut self.runs[i]) {
+            Ok(Some(next)) => self.heap.push(Reverse((next, i))),
+            Ok(None) => {}
+            Err(e) => return Some(Err(e)),
+        }
+        Some(Ok(value))
+    }
+}
+
+/// The next value and how many times it's repeated
+fn next_group(values: &mut Peekable<Merge>) -> Result<Option<(i64, i64)>, String> {
+    let Some(value) = values.next().transpose()? else {
+        return Ok(None);
+    };
+    let mut count = 1;
+    while values.next_if(|next| next.as_ref() == Ok(&value)).is_some() {
+        count += 1;
+    }
+    Ok(Some((value, count)))
+}
+
+/// Solves lists too large for memory: each list is sorted in runs written to
+/// disk, then the runs are merged back together while the answers are summed.
+pub fn day1_external(reader: impl BufRead, config: &ExternalSort) -> Result<Day1, String> {
+    if config.run_length == 0 {
+        return Err("Run length must be at least 1".to_owned());
+    }
+
+    let mut left_runs = Runs::new(config, "left")?;
+    let mut right_runs = Runs::new(config, "right")?;
+    let mut left = Vec::with_capacity(config.run_length);
+    let mut right = Vec::with_capacity(config.run_length);
+    for_each_pair(reader, |l, r| {
+        left.push(l);
+        right.push(r);
+        if left.len() == config.run_length {
+            left_runs.write(&mut left)?;
+            right_runs.write(&mut right)?;
+        }
+        Ok(())
+    })?;
+    left_runs.write(&mut left)?;
+    right_runs.write(&mut right)?;
+
+    let too_large = |name: &str| format!("{} is too large", name);
+
+    let mut diff_sum = 0_i64;
+    for (l, r) in left_runs.merge()?.zip(right_runs.merge()?) {
+        diff_sum = i64::try_from(l?.abs_diff(r?))
+            .ok()
+            .and_then(|diff| diff_sum.checked_add(diff))
+            .ok_or_else(|| too_large("Total distance"))?;
+    }
+
+    let mut similarity_score = 0_i64;
+    let mut lefts = left_runs.merge()?.peekable();
+    let mut rights = right_runs.merge()?.peekable();
+    let mut l = next_group(&mut lefts)?;
+    let mut r = next_group(&mut rights)?;
+    while let (Some((l_value, l_count)), Some((r_value, r_count))) = (l, r) {
+        match l_value.cmp(&r_value) {
+            Ordering::Less => l = next_group(&mut lefts)?,
+            Ordering::Greater => r = next_group(&mut rights)?,
+            Ordering::Equal => {
+                similarity_score = l_count
+                    .checked_mul(r_count)
+                    .and_then(|count| l_value.checked_mul(count))
+                    .and_then(|score| similarity_score.checked_add(score))
+                    .ok_or_else(|| too_large("Similarity score"))?;
+                l = next_group(&mut lefts)?;
+                r = next_group(&mut rights)?;
+            }
+        }
+    }
+
+    Ok(Day1 {
+        diff_sum,
+        similarity_score,
+    })
+}
+
+/// One left ID paired with a right ID once both lists are sorted
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pairing {
+    pub left: i64,
+    pub right: i64,
+    pub distance: u64,
+}
+
+/// What one value in the left list adds to the similarity score
+#[derive(Clone, Debug, PartialEq)]
+pub struct Contribution {
+    pub value: i64,
+    pub left_count: i64,
+    pub right_count: i64,
+    pub score: i64,
+}
+
+/// How the paired distances are spread
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stats {
+    pub min: u64,
+    pub max: u64,
+    pub median: f64,
+    /// Pairs whose distance is 0, then 1-9, 10-99 and so on, as
+    /// (lowest, highest, count)
+    pub histogram: Vec<(u64, u64, usize)>,
+    /// Left IDs which don't appear in the right list at all
+    pub unmatched_left: usize,
+}
+
+/// Every pairing and similarity contribution behind the answers, for auditing
+#[derive(Clone, Debug, PartialEq)]
+pub struct Report {
+    pub pairings: Vec<Pairing>,
+    pub contributions: Vec<Contribution>,
+    pub stats: Stats,
+}
+
+impl Report {
+    pub fn pairings_csv(&self) -> String {
+        let mut csv = String::from("left,right,distance\n");
+        for p in &self.pairings {
+            csv += &format!("{},{},{}\n", p.left, p.right, p.distance);
+        }
+        csv
+    }
+
+    pub fn contributions_csv(&self) -> String {
+        let mut csv = String::from("value,left_count,right_count,score\n");
+        for c in &self.contributions {
+            csv += &format!(
+                "{},{},{},{}\n",
+                c.value, c.left_count, c.right_count, c.score
+            );
+        }
+        csv
+    }
+}
+
+impl std::fmt::Display for Stats {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "Min distance: {}", self.min)?;
+        writeln!(f, "Max distance: {}", self.max)?;
+        writeln!(f, "Median distance: {}", self.median)?;
+        writeln!(f, "Unmatched left IDs: {}", self.unmatched_left)?;
+        write!(f, "Distances:")?;
+        for (low, high, count) in &self.histogram {
+            if low == high {
+                write!(f, "\n  {:>20}  {}", low, count)?;
+            } else {
+                write!(f, "\n  {:>20}  {}", format!("{}-{}", low, high), count)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The power of ten bucket a distance falls into, with 0 on its own
+fn bucket(distance: u64) -> (u64, u64) {
+    match distance.checked_ilog10() {
+        None => (0, 0),
+        Some(digits) => {
+            let low = 10_u64.pow(digits);
+            (low, low.checked_mul(10).map_or(u64::MAX, |high| high - 1))
+        }
+    }
+}
+
+fn stats(pairings: &[Pairing], contributions: &[Contribution]) -> Result<Stats, String> {
+    let mut distances = pairings.iter().map(|p| p.distance).collect::<Vec<u64>>();
+    distances.sort_unstable();
+    let (Some(min), Some(max)) = (distances.first(), distances.last()) else {
+        return Err("No location IDs".to_owned());
     };
 
-    input
+    let middle = distances.len() / 2;
+    let median = if distances.len() % 2 == 0 {
+        (distances[middle - 1] as f64 + distances[middle] as f64) / 2.0
+    } else {
+        distances[middle] as f64
+    };
+
+    let mut histogram: Vec<(u64, u64, usize)> = vec![];
+    for distance in &distances {
+        let (low, high) = bucket(*distance);
+        match histogram.last_mut() {
+            Some(last) if last.0 == low => last.2 += 1,
+            _ => histogram.push((low, high, 1)),
+        }
+    }
+
+    let unmatched_left = contributions
         .iter()
-        .fold(0, |sum, (l, _)| sum + l * (right_count(l) as i32))
+        .filter(|c| c.right_count == 0)
+        .map(|c| c.left_count as usize)
+        .sum();
+
+    Ok(Stats {
+        min: *min,
+        max: *max,
+        median,
+        histogram,
+        unmatched_left,
+    })
 }
 
-pub fn day1(input: String) -> Day1 {
-    let input_parsed = parse_input(input);
-    Day1 {
-        diff_sum: diff_sum(input_parsed.clone()),
-        similarity_score: similarity_score(input_parsed.clone()),
+pub fn report(input: String) -> Result<Report, String> {
+    let (mut left, mut right) = parse_input(&input)?;
+    left.sort_unstable();
+    right.sort_unstable();
+
+    let pairings = left
+        .iter()
+        .zip(right.iter())
+        .map(|(l, r)| Pairing {
+            left: *l,
+            right: *r,
+            distance: l.abs_diff(*r),
+        })
+        .collect::<Vec<Pairing>>();
+
+    let mut counts: HashMap<i64, i64> = HashMap::new();
+    right
+        .iter()
+        .for_each(|r| *counts.entry(*r).or_insert(0) += 1);
+    let mut contributions: Vec<Contribution> = vec![];
+    for value in left {
+        match contributions.last_mut() {
+            Some(last) if last.value == value => last.left_count += 1,
+            _ => contributions.push(Contribution {
+                value,
+                left_count: 1,
+                right_count: counts.get(&value).copied().unwrap_or(0),
+                score: 0,
+            }),
+        }
+    }
+    for c in contributions.iter_mut() {
+        c.score = c
+            .left_count
+            .checked_mul(c.right_count)
+            .and_then(|count| c.value.checked_mul(count))
+            .ok_or_else(|| format!("Similarity score of {} is too large", c.value))?;
     }
+
+    let stats = stats(&pairings, &contributions)?;
+    Ok(Report {
+        pairings,
+        contributions,
+        stats,
+    })
 }
 
 #[cfg(test)]
@@ -76,7 +468,7 @@ mod tests {
 3   3
 ";
 
-        let result = day1(input.to_owned());
+        let result = day1(input.to_owned()).unwrap();
 
         assert_eq!(result.diff_sum, 11);
     }
@@ -91,8 +483,74 @@ mod tests {
 3   3
 ";
 
-        let result = day1(input.to_owned());
+        let result = day1(input.to_owned()).unwrap();
+
+        assert_eq!(result.similarity_score, 31);
+    }
+
+    #[test]
+    fn reads_ids_beyond_i32() {
+        let input = "3000000000   4000000000\n4000000000   3000000000\n";
+
+        let result = day1_from_reader(std::io::Cursor::new(input)).unwrap();
+
+        assert_eq!(result.diff_sum, 0);
+        assert_eq!(result.similarity_score, 7000000000);
+    }
+
+    #[test]
+    fn sorts_on_disk() {
+        let input = r"3   4
+4   3
+2   5
+1   3
+3   9
+3   3
+";
+        let config = ExternalSort {
+            run_length: 2,
+            temp_dir: std::env::temp_dir().join(format!("aoc-day1-{}", std::process::id())),
+        };
+
+        let result = day1_external(input.as_bytes(), &config).unwrap();
+        let leftovers = fs::read_dir(&config.temp_dir).unwrap().count();
+        fs::remove_dir_all(&config.temp_dir).unwrap();
 
+        assert_eq!(result.diff_sum, 11);
         assert_eq!(result.similarity_score, 31);
+        assert_eq!(leftovers, 0);
+    }
+
+    #[test]
+    fn buckets_distances() {
+        assert_eq!(bucket(0), (0, 0));
+        assert_eq!(bucket(10), (10, 99));
+        assert_eq!(bucket(u64::MAX), (10_u64.pow(19), u64::MAX));
+    }
+
+    #[test]
+    fn reports_pairings() {
+        let input = r"3   4
+4   3
+2   5
+1   3
+3   9
+3   3
+";
+
+        let report = report(input.to_owned()).unwrap();
+
+        assert!(report
+            .pairings_csv()
+            .starts_with("left,right,distance\n1,3,2\n2,3,1\n"));
+        assert_eq!(
+            report.contributions_csv(),
+            "value,left_count,right_count,score\n1,1,0,0\n2,1,0,0\n3,3,3,27\n4,1,1,4\n"
+        );
+        assert_eq!(report.stats.min, 0);
+        assert_eq!(report.stats.max, 5);
+        assert_eq!(report.stats.median, 1.5);
+        assert_eq!(report.stats.histogram, vec![(0, 0, 1), (1, 9, 5)]);
+        assert_eq!(report.stats.unmatched_left, 2);
     }
 }
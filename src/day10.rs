@@ -1,5 +1,7 @@
 use std::collections::HashSet;
 
+use crate::parse;
+
 pub struct Day10 {
     pub part1: usize,
     pub part2: usize,
@@ -87,15 +89,28 @@ fn trails_from_point(
     trails
 }
 
-fn part1(input: String) -> usize {
-    let cells = input
-        .lines()
-        .map(|line| {
-            line.chars()
-                .map(|ch| str::parse::<usize>(ch.to_string().as_str()).unwrap())
-                .collect::<Vec<usize>>()
+/// The height of each position on the map
+pub(crate) fn parse_input(input: &str) -> Result<Vec<Vec<usize>>, String> {
+    parse::grid(input)?
+        .into_iter()
+        .enumerate()
+        .map(|(i, row)| {
+            parse::on_line(
+                i,
+                row.into_iter()
+                    .map(|ch| {
+                        ch.to_digit(10)
+                            .map(|n| n as usize)
+                            .ok_or_else(|| format!("Invalid height {:?}", ch))
+                    })
+                    .collect(),
+            )
         })
-        .collect::<Vec<Vec<usize>>>();
+        .collect()
+}
+
+pub(crate) fn part1(input: String) -> Result<usize, String> {
+    let cells = parse_input(&input)?;
 
     let height = cells.len();
     let width = cells[0].len();
@@ -117,7 +132,7 @@ fn part1(input: String) -> usize {
         })
         .collect::<Vec<(usize, usize)>>();
 
-    zeroes.iter().fold(0, |score, zero| {
+    Ok(zeroes.iter().fold(0, |score, zero| {
         let trails = trails_from_point(&board, zero, 4, vec![*zero]);
 
         let mut unique_trails = HashSet::new();
@@ -126,18 +141,11 @@ fn part1(input: String) -> usize {
         });
 
         score + unique_trails.len()
-    })
+    }))
 }
 
-fn part2(input: String) -> usize {
-    let cells = input
-        .lines()
-        .map(|line| {
-            line.chars()
-                .map(|ch| str::parse::<usize>(ch.to_string().as_str()).unwrap())
-                .collect::<Vec<usize>>()
-        })
-        .collect::<Vec<Vec<usize>>>();
+pub(crate) fn part2(input: String) -> Result<usize, String> {
+    let cells = parse_input(&input)?;
 
     let height = cells.len();
     let width = cells[0].len();
@@ -159,18 +167,18 @@ fn part2(input: String) -> usize {
         })
         .collect::<Vec<(usize, usize)>>();
 
-    zeroes.iter().fold(0, |score, zero| {
+    Ok(zeroes.iter().fold(0, |score, zero| {
         let trails = trails_from_point(&board, zero, 4, vec![*zero]);
 
         score + trails.len()
-    })
+    }))
 }
 
-pub fn day10(input: String) -> Day10 {
-    Day10 {
-        part1: part1(input.clone()),
-        part2: part2(input.clone()),
-    }
+pub fn day10(input: String) -> Result<Day10, String> {
+    Ok(Day10 {
+        part1: part1(input.clone())?,
+        part2: part2(input.clone())?,
+    })
 }
 
 #[cfg(test)]
@@ -188,7 +196,7 @@ mod tests {
 01329801
 10456732";
 
-        let result = day10(input.to_owned());
+        let result = day10(input.to_owned()).unwrap();
 
         assert_eq!(result.part1, 36);
     }
@@ -204,7 +212,7 @@ mod tests {
 01329801
 10456732";
 
-        let result = day10(input.to_owned());
+        let result = day10(input.to_owned()).unwrap();
 
         assert_eq!(result.part2, 81);
     }
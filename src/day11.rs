@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use crate::parse;
+
 pub struct Day11 {
     pub part1: usize,
     pub part2: usize,
@@ -7,7 +9,7 @@ pub struct Day11 {
 
 fn num_digits(n: i64) -> u32 {
     let mut i = 0;
-    while 10_i64.pow(i) <= n {
+    while 10_i64.checked_pow(i).is_some_and(|p| p <= n) {
         i += 1;
     }
     i
@@ -44,49 +46,61 @@ fn upsert_stone(stones: &mut HashMap<i64, usize>, stone: i64, count: usize) {
     }
 }
 
-fn blinkn(stones: Vec<i64>, n: usize) -> usize {
+fn blinkn(stones: Vec<i64>, n: usize) -> Result<usize, String> {
     let mut stones_count = HashMap::new();
     stones.iter().for_each(|stone| {
         upsert_stone(&mut stones_count, *stone, 1);
     });
 
-    let blink = |prev_count: HashMap<i64, usize>| {
+    let blink = |prev_count: HashMap<i64, usize>| -> Result<HashMap<i64, usize>, String> {
         let mut next_count = HashMap::new();
-        prev_count.iter().for_each(|(stone, count)| match stone {
-            0 => {
-                upsert_stone(&mut next_count, 1, *count);
-            }
-            s => match split_stone(*s) {
-                Some((a, b)) => {
-                    upsert_stone(&mut next_count, a, *count);
-                    upsert_stone(&mut next_count, b, *count);
+        for (stone, count) in prev_count.iter() {
+            match stone {
+                0 => {
+                    upsert_stone(&mut next_count, 1, *count);
                 }
-                None => {
-                    upsert_stone(&mut next_count, s * 2024, *count);
-                }
-            },
-        });
-        next_count
+                s => match split_stone(*s) {
+                    Some((a, b)) => {
+                        upsert_stone(&mut next_count, a, *count);
+                        upsert_stone(&mut next_count, b, *count);
+                    }
+                    None => {
+                        let next = s
+                            .checked_mul(2024)
+                            .ok_or_else(|| format!("Stone {} grows too large", s))?;
+                        upsert_stone(&mut next_count, next, *count);
+                    }
+                },
+            }
+        }
+        Ok(next_count)
     };
 
-    (0..n)
+    Ok((0..n)
         .into_iter()
-        .fold(stones_count, |prev_count, _| blink(prev_count))
+        .try_fold(stones_count, |prev_count, _| blink(prev_count))?
         .iter()
-        .fold(0, |sum, (_, count)| sum + count)
+        .fold(0, |sum, (_, count)| sum + count))
 }
 
-pub fn day11(input: String) -> Day11 {
-    let stones = input
+pub(crate) fn parse_input(input: &str) -> Result<Vec<i64>, String> {
+    input
         .trim()
         .split(' ')
-        .map(|ch| str::parse::<i64>(ch).unwrap())
-        .collect::<Vec<i64>>();
+        .map(|ch| match parse::number::<i64>(ch)? {
+            n if n < 0 => Err(format!("Negative stone {}", n)),
+            n => Ok(n),
+        })
+        .collect()
+}
 
-    Day11 {
-        part1: blinkn(stones.clone(), 25),
-        part2: blinkn(stones.clone(), 75),
-    }
+pub fn day11(input: String) -> Result<Day11, String> {
+    let stones = parse_input(&input)?;
+
+    Ok(Day11 {
+        part1: blinkn(stones.clone(), 25)?,
+        part2: blinkn(stones.clone(), 75)?,
+    })
 }
 
 #[cfg(test)]
@@ -96,7 +110,7 @@ mod tests {
     #[test]
     fn gets_part1() {
         let input = r"125 17";
-        let result = day11(input.to_owned());
+        let result = day11(input.to_owned()).unwrap();
         assert_eq!(result.part1, 55312);
     }
 }
@@ -1,5 +1,7 @@
 use std::collections::{HashMap, HashSet};
 
+use crate::parse;
+
 pub struct Day12 {
     pub part1: isize,
     pub part2: isize,
@@ -42,14 +44,11 @@ fn find_region(
     }
 }
 
-fn part1(input: String) -> isize {
+pub(crate) fn part1(input: String) -> Result<isize, String> {
     let mut recorded = HashSet::new();
     let mut regions = vec![];
 
-    let cells = input
-        .lines()
-        .map(|line| line.chars().collect::<Vec<char>>())
-        .collect::<Vec<Vec<char>>>();
+    let cells = parse::grid(&input)?;
 
     let height = cells.len() as isize;
     let width = cells[0].len() as isize;
@@ -79,7 +78,7 @@ fn part1(input: String) -> isize {
         });
     });
 
-    regions.iter().fold(0, |sum, (region, ch)| {
+    Ok(regions.iter().fold(0, |sum, (region, ch)| {
         let area = region.len() as isize;
 
         let perimiter = region.iter().fold(0, |p, (i, j)| {
@@ -108,17 +107,14 @@ fn part1(input: String) -> isize {
         });
 
         sum + area * perimiter
-    })
+    }))
 }
 
-fn part2(input: String) -> isize {
+pub(crate) fn part2(input: String) -> Result<isize, String> {
     let mut recorded = HashSet::new();
     let mut regions = vec![];
 
-    let cells = input
-        .lines()
-        .map(|line| line.chars().collect::<Vec<char>>())
-        .collect::<Vec<Vec<char>>>();
+    let cells = parse::grid(&input)?;
 
     let height = cells.len() as isize;
     let width = cells[0].len() as isize;
@@ -148,7 +144,7 @@ fn part2(input: String) -> isize {
         });
     });
 
-    regions.iter().fold(0, |sum, (region, ch)| {
+    Ok(regions.iter().fold(0, |sum, (region, ch)| {
         let area = region.len() as isize;
 
         let mut perimiter: HashMap<(i32, isize, isize), Vec<isize>> = HashMap::new();
@@ -193,14 +189,14 @@ fn part2(input: String) -> isize {
         });
 
         sum + area * sides
-    })
+    }))
 }
 
-pub fn day12(input: String) -> Day12 {
-    Day12 {
-        part1: part1(input.clone()),
-        part2: part2(input.clone()),
-    }
+pub fn day12(input: String) -> Result<Day12, String> {
+    Ok(Day12 {
+        part1: part1(input.clone())?,
+        part2: part2(input.clone())?,
+    })
 }
 
 #[cfg(test)]
@@ -219,7 +215,7 @@ VVIIICJJEE
 MIIIIIJJEE
 MIIISIJEEE
 MMMISSJEEE";
-        let result = day12(input.to_owned());
+        let result = day12(input.to_owned()).unwrap();
 
         assert_eq!(result.part1, 1930);
     }
@@ -236,7 +232,7 @@ VVIIICJJEE
 MIIIIIJJEE
 MIIISIJEEE
 MMMISSJEEE";
-        let result = day12(input.to_owned());
+        let result = day12(input.to_owned()).unwrap();
 
         assert_eq!(result.part2, 1206);
     }
@@ -1,141 +1,134 @@
+use crate::explain::Explanation;
+use crate::parse;
+
 pub struct Day13 {
     pub part1: usize,
     pub part2: usize,
 }
 
-fn part1(input: String) -> usize {
-    let (mut machines, last_machine) = input.lines().fold(
-        (vec![], ((0.0, 0.0), (0.0, 0.0), (0.0, 0.0))),
-        |(mut machines, machine), line| {
-            let get_button = || {
-                let (x_diff, y_diff) = line.split_once(": ").unwrap().1.split_once(", ").unwrap();
-                let x_diff_n = str::parse::<f32>(x_diff.split_once('+').unwrap().1).unwrap();
-                let y_diff_n = str::parse::<f32>(y_diff.split_once('+').unwrap().1).unwrap();
-                (x_diff_n, y_diff_n)
+type Machine = ((f64, f64), (f64, f64), (f64, f64));
+
+const PRIZE_OFFSET: f64 = 10000000000000.0;
+
+fn parse_pair(line: &str, prefix: &str, sign: char) -> Result<(f64, f64), String> {
+    let coordinates = line
+        .strip_prefix(prefix)
+        .ok_or_else(|| format!("Expected {:?} in {:?}", prefix, line))?;
+    let (x, y) = parse::split(coordinates, ", ")?;
+    let coordinate = |text: &str, axis: char| {
+        let value = text
+            .strip_prefix(axis)
+            .and_then(|t| t.strip_prefix(sign))
+            .ok_or_else(|| format!("Expected {}{} in {:?}", axis, sign, text))?;
+        Ok::<f64, String>(parse::number::<u32>(value)? as f64)
+    };
+    Ok((coordinate(x, 'X')?, coordinate(y, 'Y')?))
+}
+
+/// Each machine is a "Button A", "Button B" and "Prize" line, with blank
+/// lines between machines
+pub(crate) fn parse_machines(input: &str, prize_offset: f64) -> Result<Vec<Machine>, String> {
+    let lines = input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .collect::<Vec<(usize, &str)>>();
+
+    lines
+        .chunks(3)
+        .map(|machine| {
+            let line = |k: usize, prefix: &str, sign: char| match machine.get(k) {
+                Some((i, line)) => parse::on_line(*i, parse_pair(line, prefix, sign)),
+                None => Err(format!("Last machine has no {:?} line", prefix.trim())),
             };
+            let button_a = line(0, "Button A: ", '+')?;
+            let button_b = line(1, "Button B: ", '+')?;
+            let (px, py) = line(2, "Prize: ", '=')?;
+            Ok((button_a, button_b, (px + prize_offset, py + prize_offset)))
+        })
+        .collect()
+}
 
-            if line.starts_with("Button A") {
-                let button_a = get_button();
-                (machines, (button_a, (0.0, 0.0), (0.0, 0.0)))
-            } else if line.starts_with("Button B") {
-                let button_b = get_button();
-                (machines, (machine.0, button_b, (0.0, 0.0)))
-            } else if line.starts_with("Prize") {
-                let (x, y) = line.split_once(": ").unwrap().1.split_once(", ").unwrap();
-                let x_n = str::parse::<f32>(x.split_once("=").unwrap().1).unwrap();
-                let y_n = str::parse::<f32>(y.split_once("=").unwrap().1).unwrap();
-
-                (machines, (machine.0, machine.1, (x_n, y_n)))
-            } else {
-                machines.push(machine);
-                (machines, ((0.0, 0.0), (0.0, 0.0), (0.0, 0.0)))
-            }
-        },
-    );
-
-    if last_machine.0 .0 > 0.0 {
-        machines.push(last_machine);
+/// Past this, f64 can no longer tell whether a number of presses is whole
+const MAX_PRESSES: f64 = (1_u64 << f64::MANTISSA_DIGITS) as f64;
+
+/// Returns the number of (A, B) presses which win the prize, if any.
+fn presses(((ax, ay), (bx, by), (px, py)): &Machine) -> Option<(usize, usize)> {
+    // (ax bx)(Na) = (px)
+    // (ay by)(Nb)   (py)
+    //
+    // =>
+    //
+    // (Na) =       1       (by  -bx)(px)
+    // (Nb)   |ax*by-bx*ay| (-ay  ax)(py)
+    //
+    // =>
+    //
+    // Na = (by*px - bx*py) / (ax*by - bx*ay)
+    // Nb = (ax*py - ay*px) / (ax*by - bx*ay)
+
+    let na = (by * px - bx * py) / (ax * by - bx * ay);
+    let nb = (ax * py - ay * px) / (ax * by - bx * ay);
+
+    if na < 0.0 || nb < 0.0 || na.fract() != 0.0 || nb.fract() != 0.0 {
+        return None;
+    }
+    if na > MAX_PRESSES || nb > MAX_PRESSES {
+        return None;
     }
 
+    Some((na as usize, nb as usize))
+}
+
+fn cost((na, nb): (usize, usize)) -> usize {
+    3 * na + nb
+}
+
+fn total_cost(machines: Vec<Machine>) -> usize {
     machines
         .iter()
-        .fold(0, |score, ((ax, ay), (bx, by), (px, py))| {
-            // (ax bx)(Na) = (px)
-            // (ay by)(Nb)   (py)
-            //
-            // =>
-            //
-            // (Na) =       1       (by  -bx)(px)
-            // (Nb)   |ax*by-bx*ay| (-ay  ax)(py)
-            //
-            // =>
-            //
-            // Na = (by*px - bx*py) / (ax*by - bx*ay)
-            // Nb = (ax*py - ay*px) / (ax*by - bx*ay)
-
-            let na = (by * px - bx * py) / (ax * by - bx * ay);
-            let nb = (ax * py - ay * px) / (ax * by - bx * ay);
-
-            if na < 0.0 || nb < 0.0 || na.fract() != 0.0 || nb.fract() != 0.0 {
-                return score;
-            }
-
-            let cost = 3 * (na as usize) + (nb as usize);
-
-            score + cost
-        })
+        .filter_map(presses)
+        .fold(0, |score, p| score + cost(p))
 }
 
-fn part2(input: String) -> usize {
-    let (mut machines, last_machine) = input.lines().fold(
-        (vec![], ((0.0, 0.0), (0.0, 0.0), (0.0, 0.0))),
-        |(mut machines, machine), line| {
-            let get_button = || {
-                let (x_diff, y_diff) = line.split_once(": ").unwrap().1.split_once(", ").unwrap();
-                let x_diff_n = str::parse::<f64>(x_diff.split_once('+').unwrap().1).unwrap();
-                let y_diff_n = str::parse::<f64>(y_diff.split_once('+').unwrap().1).unwrap();
-                (x_diff_n, y_diff_n)
-            };
+pub(crate) fn part1(input: String) -> Result<usize, String> {
+    Ok(total_cost(parse_machines(&input, 0.0)?))
+}
 
-            if line.starts_with("Button A") {
-                let button_a = get_button();
-                (machines, (button_a, (0.0, 0.0), (0.0, 0.0)))
-            } else if line.starts_with("Button B") {
-                let button_b = get_button();
-                (machines, (machine.0, button_b, (0.0, 0.0)))
-            } else if line.starts_with("Prize") {
-                let (x, y) = line.split_once(": ").unwrap().1.split_once(", ").unwrap();
-                let x_n =
-                    str::parse::<f64>(x.split_once("=").unwrap().1).unwrap() + 10000000000000.0;
-                let y_n =
-                    str::parse::<f64>(y.split_once("=").unwrap().1).unwrap() + 10000000000000.0;
-
-                (machines, (machine.0, machine.1, (x_n, y_n)))
-            } else {
-                machines.push(machine);
-                (machines, ((0.0, 0.0), (0.0, 0.0), (0.0, 0.0)))
-            }
-        },
-    );
-
-    if last_machine.0 .0 > 0.0 {
-        machines.push(last_machine);
-    }
+pub(crate) fn part2(input: String) -> Result<usize, String> {
+    Ok(total_cost(parse_machines(&input, PRIZE_OFFSET)?))
+}
 
-    machines
-        .iter()
-        .fold(0, |score, ((ax, ay), (bx, by), (px, py))| {
-            // (ax bx)(Na) = (px)
-            // (ay by)(Nb)   (py)
-            //
-            // =>
-            //
-            // (Na) =       1       (by  -bx)(px)
-            // (Nb)   |ax*by-bx*ay| (-ay  ax)(py)
-            //
-            // =>
-            //
-            // Na = (by*px - bx*py) / (ax*by - bx*ay)
-            // Nb = (ax*py - ay*px) / (ax*by - bx*ay)
-
-            let na = (by * px - bx * py) / (ax * by - bx * ay);
-            let nb = (ax * py - ay * px) / (ax * by - bx * ay);
-
-            if na < 0.0 || nb < 0.0 || na.fract() != 0.0 || nb.fract() != 0.0 {
-                return score;
-            }
-
-            let cost = 3 * (na as usize) + (nb as usize);
-
-            score + cost
-        })
+pub fn day13(input: String) -> Result<Day13, String> {
+    Ok(Day13 {
+        part1: part1(input.clone())?,
+        part2: part2(input.clone())?,
+    })
 }
 
-pub fn day13(input: String) -> Day13 {
-    Day13 {
-        part1: part1(input.clone()),
-        part2: part2(input.clone()),
-    }
+pub fn explain(input: String) -> Result<Vec<Explanation>, String> {
+    let parts = [(1, 0.0), (2, PRIZE_OFFSET)]
+        .into_iter()
+        .map(|(part, prize_offset)| Ok((part, parse_machines(&input, prize_offset)?)))
+        .collect::<Result<Vec<(u8, Vec<Machine>)>, String>>()?;
+    Ok(parts
+        .into_iter()
+        .flat_map(|(part, machines)| {
+            machines
+                .iter()
+                .enumerate()
+                .map(|(i, machine)| {
+                    let detail = match presses(machine) {
+                        Some((na, nb)) => {
+                            format!("A x{}, B x{} for {} tokens", na, nb, cost((na, nb)))
+                        }
+                        None => "not winnable".to_owned(),
+                    };
+                    Explanation::new(part, format!("machine {}", i + 1), detail)
+                })
+                .collect::<Vec<Explanation>>()
+        })
+        .collect())
 }
 
 #[cfg(test)]
@@ -159,8 +152,34 @@ Prize: X=7870, Y=6450
 Button A: X+69, Y+23
 Button B: X+27, Y+71
 Prize: X=18641, Y=10279";
-        let result = day13(input.to_owned());
+        let result = day13(input.to_owned()).unwrap();
 
         assert_eq!(result.part1, 480);
     }
+
+    #[test]
+    fn explains_winnable_machines() {
+        let input = r"Button A: X+94, Y+34
+Button B: X+22, Y+67
+Prize: X=8400, Y=5400
+
+Button A: X+26, Y+66
+Button B: X+67, Y+21
+Prize: X=12748, Y=12176";
+        let explanations = explain(input.to_owned()).unwrap();
+
+        assert_eq!(
+            explanations,
+            vec![
+                Explanation::new(1, "machine 1", "A x80, B x40 for 280 tokens"),
+                Explanation::new(1, "machine 2", "not winnable"),
+                Explanation::new(2, "machine 1", "not winnable"),
+                Explanation::new(
+                    2,
+                    "machine 2",
+                    "A x118679050709, B x103199174542 for 459236326669 tokens"
+                ),
+            ]
+        );
+    }
 }
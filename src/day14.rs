@@ -1,5 +1,9 @@
 use std::collections::HashMap;
 
+use crate::parse;
+use crate::player::Animation;
+use crate::render::{Render, Tile, BLACK, GREEN};
+
 pub struct Day14 {
     pub part1: usize,
     pub part2: usize,
@@ -12,33 +16,46 @@ pub struct Day14Input<'a> {
     pub height: isize,
 }
 
-fn part1(
+type Vectors = Vec<((isize, isize), (isize, isize))>;
+
+fn parse_pair(text: &str, name: &str) -> Result<(isize, isize), String> {
+    let (label, pair) = parse::split(text, "=")?;
+    if label != name {
+        return Err(format!("Expected {}= in {:?}", name, text));
+    }
+    let (x, y) = parse::split(pair.trim_end(), ",")?;
+    Ok((
+        parse::number::<i32>(x)? as isize,
+        parse::number::<i32>(y)? as isize,
+    ))
+}
+
+/// The starting position and velocity of each robot, which must start
+/// inside the bathroom
+pub(crate) fn parse_robots(
     Day14Input {
         robots,
         width,
         height,
-    }: Day14Input,
-) -> usize {
-    let (q0, q1, q2, q3) = robots
-        .lines()
-        .map(|line| {
-            let (position, velocity) = line.split_once(' ').unwrap();
-            let (px_str, py_str) = position.split_once('=').unwrap().1.split_once(',').unwrap();
-            let px = str::parse::<isize>(px_str).unwrap();
-            let py = str::parse::<isize>(py_str).unwrap();
-
-            let (vx_str, vy_str) = velocity
-                .trim_end()
-                .split_once('=')
-                .unwrap()
-                .1
-                .split_once(',')
-                .unwrap();
-            let vx = str::parse::<isize>(vx_str).unwrap();
-            let vy = str::parse::<isize>(vy_str).unwrap();
-
-            ((px, py), (vx, vy))
-        })
+    }: &Day14Input,
+) -> Result<Vectors, String> {
+    if *width < 1 || *height < 1 {
+        return Err(format!("Invalid bathroom size {}x{}", width, height));
+    }
+    parse::lines(robots, |line| {
+        let (position, velocity) = parse::split(line, " ")?;
+        let (px, py) = parse_pair(position, "p")?;
+        if !(0..*width).contains(&px) || !(0..*height).contains(&py) {
+            return Err(format!("Position {},{} is outside the bathroom", px, py));
+        }
+        Ok(((px, py), parse_pair(velocity, "v")?))
+    })
+}
+
+pub(crate) fn part1(input: Day14Input) -> Result<usize, String> {
+    let Day14Input { width, height, .. } = input;
+    let (q0, q1, q2, q3) = parse_robots(&input)?
+        .into_iter()
         .map(|((px, py), (vx, vy))| {
             (
                 (px + vx * 100).rem_euclid(width),
@@ -64,33 +81,43 @@ fn part1(
             }
         });
 
-    q0 * q1 * q2 * q3
+    Ok(q0 * q1 * q2 * q3)
 }
 
-type Vectors = Vec<((isize, isize), (isize, isize))>;
+/// Robot positions at one instant, drawn as a map of the bathroom
+pub struct Robots {
+    width: usize,
+    height: usize,
+    counts: Vec<usize>,
+}
 
-fn print_board(Day14Input { width, height, .. }: &Day14Input<'_>, vectors: &Vectors) {
-    for j in (0..*height).into_iter() {
-        println!(
-            "{}",
-            (0..*width)
-                .enumerate()
-                .map(|(i, _)| {
-                    let num_robots = vectors
-                        .iter()
-                        .filter(|((px, py), _)| *px == i as isize && *py == j)
-                        .count();
-
-                    if num_robots == 0 {
-                        format!(".")
-                    } else {
-                        format!("o")
-                        // format!("{}", num_robots)
-                    }
-                })
-                .collect::<Vec<String>>()
-                .join("")
-        );
+impl Robots {
+    fn new(width: isize, height: isize, vectors: &Vectors) -> Self {
+        let width = width as usize;
+        let height = height as usize;
+        let mut counts = vec![0; width * height];
+        vectors.iter().for_each(|((px, py), _)| {
+            counts[(*py as usize) * width + (*px as usize)] += 1;
+        });
+        Robots {
+            width,
+            height,
+            counts,
+        }
+    }
+}
+
+impl Render for Robots {
+    fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    fn tile(&self, x: usize, y: usize) -> Tile {
+        if self.counts[y * self.width + x] == 0 {
+            Tile::new('.', BLACK)
+        } else {
+            Tile::new('o', GREEN)
+        }
     }
 }
 
@@ -131,33 +158,11 @@ fn is_christmas_tree(vectors: &Vectors) -> bool {
     row_sizes.len() > 3
 }
 
-fn part2(input: Day14Input) -> usize {
-    let Day14Input {
-        robots,
-        width,
-        height,
-    } = input;
-    let vectors = robots
-        .lines()
-        .map(|line| {
-            let (position, velocity) = line.split_once(' ').unwrap();
-            let (px_str, py_str) = position.split_once('=').unwrap().1.split_once(',').unwrap();
-            let px = str::parse::<isize>(px_str).unwrap();
-            let py = str::parse::<isize>(py_str).unwrap();
-
-            let (vx_str, vy_str) = velocity
-                .trim_end()
-                .split_once('=')
-                .unwrap()
-                .1
-                .split_once(',')
-                .unwrap();
-            let vx = str::parse::<isize>(vx_str).unwrap();
-            let vy = str::parse::<isize>(vy_str).unwrap();
-
-            ((px, py), (vx, vy))
-        })
-        .collect::<Vectors>();
+/// Steps the robots until they form a christmas tree, returning the number
+/// of seconds taken along with their positions at that moment.
+fn find_christmas_tree(input: &Day14Input) -> Result<(usize, Vectors), String> {
+    let Day14Input { width, height, .. } = input.clone();
+    let vectors = parse_robots(input)?;
 
     let tick = |prev_vectors: Vectors| -> Vectors {
         prev_vectors
@@ -175,26 +180,76 @@ fn part2(input: Day14Input) -> usize {
     };
 
     let mut next = vectors;
-    for i in (0..100000).into_iter() {
-        print!("{esc}[2J{esc}[1;1H", esc = 27 as char);
+    for i in 0..100000 {
         next = tick(next);
-        println!("i={}", i);
         if is_christmas_tree(&next) {
-            print_board(&input, &next);
-            return i + 1;
+            return Ok((i + 1, next));
         }
     }
 
-    panic!("Could not find christmas tree");
+    Err("Could not find christmas tree".to_owned())
+}
+
+pub(crate) fn part2(input: Day14Input) -> Result<usize, String> {
+    Ok(find_christmas_tree(&input)?.0)
 }
 
-pub fn day14(input: Day14Input) -> Day14 {
-    Day14 {
-        part1: part1(input.clone()),
-        part2: part2(input.clone()),
+/// The robots at the moment they form a christmas tree
+pub fn render_state(input: Day14Input) -> Result<Robots, String> {
+    let (_, vectors) = find_christmas_tree(&input)?;
+    Ok(Robots::new(input.width, input.height, &vectors))
+}
+
+pub struct RobotsAnimation {
+    width: isize,
+    height: isize,
+    vectors: Vectors,
+    len: usize,
+}
+
+impl Animation for RobotsAnimation {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn frame(&self, step: usize) -> Box<dyn Render + '_> {
+        let (width, height) = (self.width, self.height);
+        let t = step as isize;
+        let vectors = self
+            .vectors
+            .iter()
+            .map(|((px, py), v)| {
+                (
+                    (
+                        (px + v.0 * t).rem_euclid(width),
+                        (py + v.1 * t).rem_euclid(height),
+                    ),
+                    *v,
+                )
+            })
+            .collect::<Vectors>();
+        Box::new(Robots::new(width, height, &vectors))
     }
 }
 
+/// Every second from the start until the robots form a christmas tree
+pub fn animation(input: Day14Input) -> Result<RobotsAnimation, String> {
+    let (seconds, _) = find_christmas_tree(&input)?;
+    Ok(RobotsAnimation {
+        width: input.width,
+        height: input.height,
+        vectors: parse_robots(&input)?,
+        len: seconds + 1,
+    })
+}
+
+pub fn day14(input: Day14Input) -> Result<Day14, String> {
+    Ok(Day14 {
+        part1: part1(input.clone())?,
+        part2: part2(input.clone())?,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -218,7 +273,7 @@ p=9,5 v=-3,-3",
             height: 7,
         };
 
-        let result = part1(input);
+        let result = part1(input).unwrap();
         assert_eq!(result, 12);
     }
 }
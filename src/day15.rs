@@ -1,3 +1,7 @@
+use crate::parse;
+use crate::player::Animation;
+use crate::render::{Render, Tile, BLACK, RED, WHITE, YELLOW};
+
 pub struct Day15 {
     pub part1: usize,
     pub part2: usize,
@@ -25,36 +29,65 @@ struct Board {
 
 type Program = Vec<i8>;
 
-fn parse_input(input: String) -> (Board, Program) {
-    let lines_board = input.lines().take_while(|line| line.trim().len() > 0);
-    let program = input
+/// The warehouse map, which must be walled all round with exactly one robot,
+/// and the robot's moves, which may be split across several lines.
+pub(crate) fn split_input(input: &str) -> Result<(Vec<Vec<char>>, Program), String> {
+    let map_len = input
         .lines()
-        .skip_while(|line| line.trim().len() > 0)
-        .skip(1)
-        .flat_map(|line| {
-            line.trim()
-                .chars()
-                .map(|ch| match ch {
-                    '^' => UP,
-                    '>' => RIGHT,
-                    'v' => DOWN,
-                    '<' => LEFT,
-                    _ => panic!("Invalid character {}", ch),
-                })
-                .collect::<Vec<i8>>()
-        })
-        .collect::<Vec<i8>>();
+        .take_while(|line| !line.trim().is_empty())
+        .count();
+    let map = input
+        .lines()
+        .take(map_len)
+        .collect::<Vec<&str>>()
+        .join("\n");
+    let cells = parse::grid(&map)?;
+    parse::only_chars(&cells, "#.O@")?;
+    parse::find_one(&cells, '@')?;
+
+    let last = cells.len() - 1;
+    for (j, row) in cells.iter().enumerate() {
+        let walled = if j == 0 || j == last {
+            row.iter().all(|ch| *ch == '#')
+        } else {
+            row[0] == '#' && row[row.len() - 1] == '#'
+        };
+        if !walled {
+            return parse::on_line(j, Err("Warehouse is not walled in".to_owned()));
+        }
+    }
+
+    let mut program = vec![];
+    for (j, line) in input.lines().enumerate().skip(map_len + 1) {
+        for ch in line.trim().chars() {
+            let step = match ch {
+                '^' => UP,
+                '>' => RIGHT,
+                'v' => DOWN,
+                '<' => LEFT,
+                _ => return parse::on_line(j, Err(format!("Invalid move {:?}", ch))),
+            };
+            program.push(step);
+        }
+    }
+
+    Ok((cells, program))
+}
+
+fn parse_input(input: String) -> Result<(Board, Program), String> {
+    let (lines_board, program) = split_input(&input)?;
 
-    let height = lines_board.clone().count();
-    let width = lines_board.clone().next().unwrap().chars().count();
+    let height = lines_board.len();
+    let width = lines_board[0].len();
     let mut robot = (0, 0);
     let cells = lines_board
+        .iter()
         .enumerate()
         .map(|(j, line)| {
-            line.chars()
+            line.iter()
                 .enumerate()
                 .map(|(i, ch)| {
-                    if ch == '@' {
+                    if *ch == '@' {
                         robot = (i, j);
                     }
                     match ch {
@@ -74,38 +107,23 @@ fn parse_input(input: String) -> (Board, Program) {
         robot,
     };
 
-    (board, program)
+    Ok((board, program))
 }
 
-fn parse_input_wide(input: String) -> (Board, Program) {
-    let lines_board = input.lines().take_while(|line| line.trim().len() > 0);
-    let program = input
-        .lines()
-        .skip_while(|line| line.trim().len() > 0)
-        .skip(1)
-        .flat_map(|line| {
-            line.chars()
-                .map(|ch| match ch {
-                    '^' => UP,
-                    '>' => RIGHT,
-                    'v' => DOWN,
-                    '<' => LEFT,
-                    _ => panic!("Invalid character {}", ch),
-                })
-                .collect::<Vec<i8>>()
-        })
-        .collect::<Vec<i8>>();
+fn parse_input_wide(input: String) -> Result<(Board, Program), String> {
+    let (lines_board, program) = split_input(&input)?;
 
-    let height = lines_board.clone().count();
-    let width = lines_board.clone().next().unwrap().chars().count() * 2;
+    let height = lines_board.len();
+    let width = lines_board[0].len() * 2;
     let mut robot = (0, 0);
     let cells = lines_board
+        .iter()
         .enumerate()
         .map(|(j, line)| {
-            line.chars()
+            line.iter()
                 .enumerate()
                 .flat_map(|(i, ch)| {
-                    if ch == '@' {
+                    if *ch == '@' {
                         robot = (2 * i, j);
                     }
                     match ch {
@@ -125,153 +143,158 @@ fn parse_input_wide(input: String) -> (Board, Program) {
         robot,
     };
 
-    (board, program)
+    Ok((board, program))
 }
 
-fn print_board(board: &Board) {
-    print!("\x1B[2J\x1B[1;1H");
-    board.cells.iter().enumerate().for_each(|(j, row)| {
-        row.iter().enumerate().for_each(|(i, ch)| {
-            if i == board.robot.0 && j == board.robot.1 {
-                if *ch != EMPTY {
-                    panic!("Robot on non-empty square {},{}", i, j);
-                }
-                print!("@");
-            } else {
-                match *ch {
-                    EMPTY => print!("."),
-                    WALL => print!("#"),
-                    BOX => print!("O"),
-                    BOX_L => print!("["),
-                    BOX_R => print!("]"),
-                    _ => panic!(),
-                };
+impl Render for Board {
+    fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    fn tile(&self, x: usize, y: usize) -> Tile {
+        if (x, y) == self.robot {
+            if self.cells[y][x] != EMPTY {
+                panic!("Robot on non-empty square {},{}", x, y);
             }
-        });
-        print!("\n");
+            return Tile::new('@', RED);
+        }
+        match self.cells[y][x] {
+            EMPTY => Tile::new('.', BLACK),
+            WALL => Tile::new('#', WHITE),
+            BOX => Tile::new('O', YELLOW),
+            BOX_L => Tile::new('[', YELLOW),
+            BOX_R => Tile::new(']', YELLOW),
+            _ => panic!(),
+        }
+    }
+}
+
+fn gps_sum(board: &Board, box_cell: i8) -> usize {
+    board.cells.iter().enumerate().fold(0, |sum0, (j, row)| {
+        row.iter()
+            .enumerate()
+            .filter(|(_, cell)| **cell == box_cell)
+            .fold(sum0, |sum1, (i, _)| sum1 + i + 100 * j)
     })
 }
 
-fn part1(input: String) -> usize {
-    let (board, program) = parse_input(input);
-
-    let result = program.iter().fold(board, |prev, step| {
-        let Board {
-            height,
-            width,
-            cells,
-            robot: (rx, ry),
-        } = prev;
-        let mut next = cells.clone();
-        match *step {
-            UP => {
-                let mut j = ry - 1;
-                while j > 0 && cells[j][rx] != EMPTY && cells[j][rx] != WALL {
-                    j -= 1;
-                }
-                if cells[j][rx] == WALL {
-                    return Board {
-                        height,
-                        width,
-                        cells,
-                        robot: (rx, ry),
-                    };
-                }
-                next[ry][rx] = EMPTY;
-                for k in j..(ry - 1) {
-                    next[k][rx] = cells[k + 1][rx];
-                }
-                Board {
+fn move_robot(prev: Board, step: &i8) -> Board {
+    let Board {
+        height,
+        width,
+        cells,
+        robot: (rx, ry),
+    } = prev;
+    let mut next = cells.clone();
+    match *step {
+        UP => {
+            let mut j = ry - 1;
+            while j > 0 && cells[j][rx] != EMPTY && cells[j][rx] != WALL {
+                j -= 1;
+            }
+            if cells[j][rx] == WALL {
+                return Board {
                     height,
                     width,
-                    cells: next,
-                    robot: (rx, ry - 1),
-                }
+                    cells,
+                    robot: (rx, ry),
+                };
             }
-            RIGHT => {
-                let mut i = rx + 1;
-                while i < width - 1 && cells[ry][i] != EMPTY && cells[ry][i] != WALL {
-                    i += 1;
-                }
-                if cells[ry][i] == WALL {
-                    return Board {
-                        height,
-                        width,
-                        cells,
-                        robot: (rx, ry),
-                    };
-                }
-                next[ry][rx] = EMPTY;
-                for l in (rx + 1)..i {
-                    next[ry][l + 1] = cells[ry][l];
-                }
-                Board {
+            next[ry][rx] = EMPTY;
+            for k in j..(ry - 1) {
+                next[k][rx] = cells[k + 1][rx];
+            }
+            Board {
+                height,
+                width,
+                cells: next,
+                robot: (rx, ry - 1),
+            }
+        }
+        RIGHT => {
+            let mut i = rx + 1;
+            while i < width - 1 && cells[ry][i] != EMPTY && cells[ry][i] != WALL {
+                i += 1;
+            }
+            if cells[ry][i] == WALL {
+                return Board {
                     height,
                     width,
-                    cells: next,
-                    robot: (rx + 1, ry),
-                }
+                    cells,
+                    robot: (rx, ry),
+                };
             }
-            DOWN => {
-                let mut j = ry + 1;
-                while j < height - 1 && cells[j][rx] != EMPTY && cells[j][rx] != WALL {
-                    j += 1;
-                }
-                if cells[j][rx] == WALL {
-                    return Board {
-                        height,
-                        width,
-                        cells,
-                        robot: (rx, ry),
-                    };
-                }
-                next[ry][rx] = EMPTY;
-                for k in (ry + 1)..j {
-                    next[k + 1][rx] = cells[k][rx];
-                }
-                Board {
+            next[ry][rx] = EMPTY;
+            for l in (rx + 1)..i {
+                next[ry][l + 1] = cells[ry][l];
+            }
+            Board {
+                height,
+                width,
+                cells: next,
+                robot: (rx + 1, ry),
+            }
+        }
+        DOWN => {
+            let mut j = ry + 1;
+            while j < height - 1 && cells[j][rx] != EMPTY && cells[j][rx] != WALL {
+                j += 1;
+            }
+            if cells[j][rx] == WALL {
+                return Board {
                     height,
                     width,
-                    cells: next,
-                    robot: (rx, ry + 1),
-                }
+                    cells,
+                    robot: (rx, ry),
+                };
             }
-            LEFT => {
-                let mut i = rx - 1;
-                while i > 0 && cells[ry][i] != EMPTY && cells[ry][i] != WALL {
-                    i -= 1;
-                }
-                if cells[ry][i] == WALL {
-                    return Board {
-                        height,
-                        width,
-                        cells,
-                        robot: (rx, ry),
-                    };
-                }
-                next[ry][rx] = EMPTY;
-                for l in i..(rx - 1) {
-                    next[ry][l] = cells[ry][l + 1];
-                }
-                Board {
+            next[ry][rx] = EMPTY;
+            for k in (ry + 1)..j {
+                next[k + 1][rx] = cells[k][rx];
+            }
+            Board {
+                height,
+                width,
+                cells: next,
+                robot: (rx, ry + 1),
+            }
+        }
+        LEFT => {
+            let mut i = rx - 1;
+            while i > 0 && cells[ry][i] != EMPTY && cells[ry][i] != WALL {
+                i -= 1;
+            }
+            if cells[ry][i] == WALL {
+                return Board {
                     height,
                     width,
-                    cells: next,
-                    robot: (rx - 1, ry),
-                }
+                    cells,
+                    robot: (rx, ry),
+                };
+            }
+            next[ry][rx] = EMPTY;
+            for l in i..(rx - 1) {
+                next[ry][l] = cells[ry][l + 1];
+            }
+            Board {
+                height,
+                width,
+                cells: next,
+                robot: (rx - 1, ry),
             }
-            _ => panic!(),
         }
-    });
+        _ => panic!(),
+    }
+}
 
-    print_board(&result);
+fn simulate(input: String) -> Result<Board, String> {
+    let (board, program) = parse_input(input)?;
 
-    result.cells.iter().enumerate().fold(0, |sum0, (j, row)| {
-        row.iter()
-            .enumerate()
-            .filter(|(_, cell)| **cell == BOX)
-            .fold(sum0, |sum1, (i, _)| sum1 + i + 100 * j)
-    })
+    Ok(program.iter().fold(board, move_robot))
+}
+
+pub(crate) fn part1(input: String) -> Result<usize, String> {
+    Ok(gps_sum(&simulate(input)?, BOX))
 }
 
 fn detect_collision(
@@ -308,187 +331,225 @@ fn detect_collision(
     }
 }
 
-fn part2(input: String) -> usize {
-    let (board, program) = parse_input_wide(input);
-
-    print_board(&board);
-
-    let result = program.iter().fold(board, |prev, step| {
-        let Board {
-            height,
-            width,
-            robot: (rx, ry),
-            ..
-        } = prev;
-        let mut cells = prev.cells.clone();
-        let next = match *step {
-            UP => match cells[ry - 1][rx] {
-                EMPTY => Board {
-                    height,
-                    width,
-                    cells,
-                    robot: (rx, ry - 1),
-                },
-                WALL => Board {
-                    height,
-                    width,
-                    cells,
-                    robot: (rx, ry),
-                },
-                BOX_L | BOX_R => {
-                    let box0 = if cells[ry - 1][rx] == BOX_L {
-                        (rx, ry - 1)
-                    } else {
-                        (rx - 1, ry - 1)
-                    };
-
-                    let collisions = detect_collision(&prev, -1, box0);
-
-                    if let Some(boxes_to_move) = collisions {
-                        boxes_to_move.iter().for_each(|(bx, by)| {
-                            cells[*by][*bx] = EMPTY;
-                            cells[*by][bx + 1] = EMPTY;
-                        });
-                        boxes_to_move.iter().for_each(|(bx, by)| {
-                            cells[by - 1][*bx] = BOX_L;
-                            cells[by - 1][bx + 1] = BOX_R;
-                        });
-
-                        Board {
-                            height,
-                            width,
-                            cells,
-                            robot: (rx, ry - 1),
-                        }
-                    } else {
-                        Board {
-                            height,
-                            width,
-                            cells,
-                            robot: (rx, ry),
-                        }
-                    }
-                }
-                _ => panic!(),
+fn move_robot_wide(prev: Board, step: &i8) -> Board {
+    let Board {
+        height,
+        width,
+        robot: (rx, ry),
+        ..
+    } = prev;
+    let mut cells = prev.cells.clone();
+    let next = match *step {
+        UP => match cells[ry - 1][rx] {
+            EMPTY => Board {
+                height,
+                width,
+                cells,
+                robot: (rx, ry - 1),
             },
-            RIGHT => {
-                let mut i = rx + 1;
-                while i < width - 1 && cells[ry][i] != EMPTY && cells[ry][i] != WALL {
-                    i += 1;
-                }
-                if cells[ry][i] == WALL {
-                    return Board {
+            WALL => Board {
+                height,
+                width,
+                cells,
+                robot: (rx, ry),
+            },
+            BOX_L | BOX_R => {
+                let box0 = if cells[ry - 1][rx] == BOX_L {
+                    (rx, ry - 1)
+                } else {
+                    (rx - 1, ry - 1)
+                };
+
+                let collisions = detect_collision(&prev, -1, box0);
+
+                if let Some(boxes_to_move) = collisions {
+                    boxes_to_move.iter().for_each(|(bx, by)| {
+                        cells[*by][*bx] = EMPTY;
+                        cells[*by][bx + 1] = EMPTY;
+                    });
+                    boxes_to_move.iter().for_each(|(bx, by)| {
+                        cells[by - 1][*bx] = BOX_L;
+                        cells[by - 1][bx + 1] = BOX_R;
+                    });
+
+                    Board {
+                        height,
+                        width,
+                        cells,
+                        robot: (rx, ry - 1),
+                    }
+                } else {
+                    Board {
                         height,
                         width,
                         cells,
                         robot: (rx, ry),
-                    };
-                }
-                for l in (rx + 1)..i {
-                    cells[ry][i - (l - rx - 1)] = cells[ry][i - (l - rx)];
-                }
-                cells[ry][rx + 1] = EMPTY;
-                Board {
-                    height,
-                    width,
-                    cells,
-                    robot: (rx + 1, ry),
+                    }
                 }
             }
-            DOWN => match cells[ry + 1][rx] {
-                EMPTY => Board {
-                    height,
-                    width,
-                    cells,
-                    robot: (rx, ry + 1),
-                },
-                WALL => Board {
+            _ => panic!(),
+        },
+        RIGHT => {
+            let mut i = rx + 1;
+            while i < width - 1 && cells[ry][i] != EMPTY && cells[ry][i] != WALL {
+                i += 1;
+            }
+            if cells[ry][i] == WALL {
+                return Board {
                     height,
                     width,
                     cells,
                     robot: (rx, ry),
-                },
-                BOX_L | BOX_R => {
-                    let box0 = if cells[ry + 1][rx] == BOX_L {
-                        (rx, ry + 1)
-                    } else {
-                        (rx - 1, ry + 1)
-                    };
-
-                    if let Some(boxes_to_move) = detect_collision(&prev, 1, box0) {
-                        boxes_to_move.iter().for_each(|(bx, by)| {
-                            cells[*by][*bx] = EMPTY;
-                            cells[*by][bx + 1] = EMPTY;
-                        });
-                        boxes_to_move.iter().for_each(|(bx, by)| {
-                            cells[by + 1][*bx] = BOX_L;
-                            cells[by + 1][bx + 1] = BOX_R;
-                        });
-
-                        Board {
-                            height,
-                            width,
-                            cells,
-                            robot: (rx, ry + 1),
-                        }
-                    } else {
-                        Board {
-                            height,
-                            width,
-                            cells,
-                            robot: (rx, ry),
-                        }
-                    }
-                }
-                _ => panic!(),
+                };
+            }
+            for l in (rx + 1)..i {
+                cells[ry][i - (l - rx - 1)] = cells[ry][i - (l - rx)];
+            }
+            cells[ry][rx + 1] = EMPTY;
+            Board {
+                height,
+                width,
+                cells,
+                robot: (rx + 1, ry),
+            }
+        }
+        DOWN => match cells[ry + 1][rx] {
+            EMPTY => Board {
+                height,
+                width,
+                cells,
+                robot: (rx, ry + 1),
             },
-            LEFT => {
-                let mut i = rx - 1;
-                while i > 0 && cells[ry][i] != EMPTY && cells[ry][i] != WALL {
-                    i -= 1;
-                }
-                if cells[ry][i] == WALL {
-                    return Board {
+            WALL => Board {
+                height,
+                width,
+                cells,
+                robot: (rx, ry),
+            },
+            BOX_L | BOX_R => {
+                let box0 = if cells[ry + 1][rx] == BOX_L {
+                    (rx, ry + 1)
+                } else {
+                    (rx - 1, ry + 1)
+                };
+
+                if let Some(boxes_to_move) = detect_collision(&prev, 1, box0) {
+                    boxes_to_move.iter().for_each(|(bx, by)| {
+                        cells[*by][*bx] = EMPTY;
+                        cells[*by][bx + 1] = EMPTY;
+                    });
+                    boxes_to_move.iter().for_each(|(bx, by)| {
+                        cells[by + 1][*bx] = BOX_L;
+                        cells[by + 1][bx + 1] = BOX_R;
+                    });
+
+                    Board {
+                        height,
+                        width,
+                        cells,
+                        robot: (rx, ry + 1),
+                    }
+                } else {
+                    Board {
                         height,
                         width,
                         cells,
                         robot: (rx, ry),
-                    };
-                }
-                cells[ry][rx] = EMPTY;
-                for l in i..rx {
-                    cells[ry][l] = cells[ry][l + 1];
+                    }
                 }
-                Board {
+            }
+            _ => panic!(),
+        },
+        LEFT => {
+            let mut i = rx - 1;
+            while i > 0 && cells[ry][i] != EMPTY && cells[ry][i] != WALL {
+                i -= 1;
+            }
+            if cells[ry][i] == WALL {
+                return Board {
                     height,
                     width,
                     cells,
-                    robot: (rx - 1, ry),
-                }
+                    robot: (rx, ry),
+                };
             }
-            _ => panic!(),
-        };
+            cells[ry][rx] = EMPTY;
+            for l in i..rx {
+                cells[ry][l] = cells[ry][l + 1];
+            }
+            Board {
+                height,
+                width,
+                cells,
+                robot: (rx - 1, ry),
+            }
+        }
+        _ => panic!(),
+    };
 
-        next
-    });
+    next
+}
 
-    print_board(&result);
+fn simulate_wide(input: String) -> Result<Board, String> {
+    let (board, program) = parse_input_wide(input)?;
 
-    result.cells.iter().enumerate().fold(0, |sum0, (j, row)| {
-        row.iter()
-            .enumerate()
-            .filter(|(_, cell)| **cell == BOX_L)
-            .fold(sum0, |sum1, (i, _)| sum1 + i + 100 * j)
-    })
+    Ok(program.iter().fold(board, move_robot_wide))
+}
+
+pub(crate) fn part2(input: String) -> Result<usize, String> {
+    Ok(gps_sum(&simulate_wide(input)?, BOX_L))
+}
+
+/// The wide warehouse after the robot has followed every instruction
+pub fn render_state(input: String) -> Result<impl Render, String> {
+    simulate_wide(input)
+}
+
+const CHECKPOINT_INTERVAL: usize = 64;
+
+/// Replays the wide warehouse, keeping a board every few steps so any step
+/// can be reached without simulating from the start.
+pub struct WarehouseAnimation {
+    program: Program,
+    checkpoints: Vec<Board>,
 }
 
-pub fn day15(input: String) -> Day15 {
-    Day15 {
-        part1: part1(input.clone()),
-        part2: part2(input.clone()),
+impl Animation for WarehouseAnimation {
+    fn len(&self) -> usize {
+        self.program.len() + 1
+    }
+
+    fn frame(&self, step: usize) -> Box<dyn Render + '_> {
+        let checkpoint = step / CHECKPOINT_INTERVAL;
+        let board = self.program[checkpoint * CHECKPOINT_INTERVAL..step]
+            .iter()
+            .fold(self.checkpoints[checkpoint].clone(), move_robot_wide);
+        Box::new(board)
     }
 }
 
+/// The robot following each instruction in the wide warehouse
+pub fn animation(input: String) -> Result<WarehouseAnimation, String> {
+    let (board, program) = parse_input_wide(input)?;
+    let mut checkpoints = vec![board];
+    for steps in program.chunks(CHECKPOINT_INTERVAL) {
+        let last = checkpoints.last().unwrap().clone();
+        checkpoints.push(steps.iter().fold(last, move_robot_wide));
+    }
+
+    Ok(WarehouseAnimation {
+        program,
+        checkpoints,
+    })
+}
+
+pub fn day15(input: String) -> Result<Day15, String> {
+    Ok(Day15 {
+        part1: part1(input.clone())?,
+        part2: part2(input.clone())?,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -516,7 +577,7 @@ vvv<<^>^v^^><<>>><>^<<><^vv^^<>vvv<>><^^v>^>vv<>v<<<<v<^v>^<^^>>>^<v<v
 <><^^>^^^<><vvvvv^v<v<<>^v<v>v<<^><<><<><<<^^<<<^<<>><<><^^^>^^<>^>v<>
 ^^>vv<^v^v<vv>^<><v<^v>^^^>>>^^vvv^>vvv<>>>^<^>>>>>^<<^v>^vvv<>^<><<v>
 v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^";
-        let result = day15(input.to_owned());
+        let result = day15(input.to_owned()).unwrap();
         assert_eq!(result.part1, 10092);
     }
 
@@ -543,7 +604,38 @@ vvv<<^>^v^^><<>>><>^<<><^vv^^<>vvv<>><^^v>^>vv<>v<<<<v<^v>^<^^>>>^<v<v
 <><^^>^^^<><vvvvv^v<v<<>^v<v>v<<^><<><<><<<^^<<<^<<>><<><^^^>^^<>^>v<>
 ^^>vv<^v^v<vv>^<><v<^v>^^^>>>^^vvv^>vvv<>>>^<^>>>>>^<<^v>^vvv<>^<><<v>
 v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^";
-        let result = day15(input.to_owned());
+        let result = day15(input.to_owned()).unwrap();
         assert_eq!(result.part2, 9021);
     }
+
+    #[test]
+    fn animates_wide_warehouse() {
+        let input = r"#######
+#...#.#
+#.....#
+#..OO@#
+#..O..#
+#.....#
+#######
+
+<vvv<<^^<<^^";
+        let animation = animation(input.to_owned()).unwrap();
+        assert_eq!(animation.len(), 13);
+        assert_eq!(
+            animation.frame(0).to_text(),
+            simulate_wide(input.replace("<vvv<<^^<<^^", ""))
+                .unwrap()
+                .to_text()
+        );
+        assert_eq!(
+            animation.frame(12).to_text(),
+            r"##############
+##...[].##..##
+##...@.[]...##
+##....[]....##
+##..........##
+##..........##
+##############"
+        );
+    }
 }
@@ -1,6 +1,9 @@
 use core::fmt;
 use std::collections::{HashMap, HashSet};
 
+use crate::parse;
+use crate::render::{Render, Tile, BLACK, BLUE, GREY, RED};
+
 pub struct Day16 {
     pub part1: usize,
     pub part2: usize,
@@ -36,7 +39,7 @@ impl fmt::Debug for Position {
 }
 
 #[derive(Clone)]
-struct Board {
+pub(crate) struct Board {
     height: usize,
     width: usize,
     cells: Vec<Vec<i8>>,
@@ -44,46 +47,44 @@ struct Board {
     goal: (usize, usize),
 }
 
+impl Render for Board {
+    fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    fn tile(&self, x: usize, y: usize) -> Tile {
+        if self.position == Position(x, y, self.position.2) {
+            Tile::new('S', BLUE)
+        } else if self.goal == (x, y) {
+            Tile::new('E', RED)
+        } else {
+            match self.cells[y][x] {
+                EMPTY => Tile::new('.', BLACK),
+                WALL => Tile::new('#', GREY),
+                _ => panic!(),
+            }
+        }
+    }
+}
+
 impl fmt::Debug for Board {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "Board:\n{}",
-            (0..self.height)
-                .into_iter()
-                .enumerate()
-                .map(|(j, _)| {
-                    (0..self.width)
-                        .into_iter()
-                        .enumerate()
-                        .map(|(i, _)| {
-                            if self.position == Position(i, j, self.position.2) {
-                                "S"
-                            } else if self.goal == (i, j) {
-                                "E"
-                            } else {
-                                match self.cells[j][i] {
-                                    EMPTY => ".",
-                                    WALL => "#",
-                                    _ => panic!(),
-                                }
-                            }
-                        })
-                        .collect::<String>()
-                })
-                .collect::<Vec<String>>()
-                .join("\n")
-        )
+        write!(f, "Board:\n{}", self.to_text())
     }
 }
 
-fn parse_board(input: String) -> Board {
-    let height = input.lines().count();
-    let width = input.lines().next().unwrap().chars().count();
-    let cells = input
-        .lines()
-        .map(|line| {
-            line.chars()
+pub(crate) fn parse_board(input: String) -> Result<Board, String> {
+    let grid = parse::grid(&input)?;
+    parse::only_chars(&grid, "#.SE")?;
+    let (sx, sy) = parse::find_one(&grid, 'S')?;
+    let goal = parse::find_one(&grid, 'E')?;
+
+    let height = grid.len();
+    let width = grid[0].len();
+    let cells = grid
+        .iter()
+        .map(|row| {
+            row.iter()
                 .map(|ch| match ch {
                     '#' => WALL,
                     _ => EMPTY,
@@ -91,31 +92,14 @@ fn parse_board(input: String) -> Board {
                 .collect::<Vec<i8>>()
         })
         .collect::<Vec<Vec<i8>>>();
-    let (position, goal) =
-        input
-            .lines()
-            .enumerate()
-            .fold((None, None), |(prev_position, prev_goal), (j, line)| {
-                if prev_position.is_some() && prev_goal.is_some() {
-                    (prev_position, prev_goal)
-                } else {
-                    line.chars()
-                        .enumerate()
-                        .fold((prev_position, prev_goal), |next, (i, ch)| match ch {
-                            'S' => (Some(Position(i, j, EAST)), next.1),
-                            'E' => (next.0, Some((i, j))),
-                            _ => next,
-                        })
-                }
-            });
 
-    Board {
+    Ok(Board {
         height,
         width,
         cells,
-        position: position.unwrap(),
-        goal: goal.unwrap(),
-    }
+        position: Position(sx, sy, EAST),
+        goal,
+    })
 }
 
 fn get_next_position(board: &Board, Position(x, y, dir): Position) -> Option<(Position, i8)> {
@@ -152,6 +136,10 @@ fn get_next_position(board: &Board, Position(x, y, dir): Position) -> Option<(Po
     }
 }
 
+fn no_path() -> String {
+    "No path from S to E".to_owned()
+}
+
 #[derive(Clone, Debug)]
 struct StackElem {
     position: Position,
@@ -159,8 +147,8 @@ struct StackElem {
     tail: HashSet<(usize, usize)>,
 }
 
-fn part1(input: String) -> usize {
-    let board = parse_board(input);
+pub(crate) fn part1(input: String) -> Result<usize, String> {
+    let board = parse_board(input)?;
     let mut solutions = vec![];
 
     let mut stack = vec![StackElem {
@@ -238,11 +226,11 @@ fn part1(input: String) -> usize {
     }
 
     solutions.sort();
-    *solutions.first().unwrap()
+    solutions.first().copied().ok_or_else(no_path)
 }
 
-fn part2(input: String) -> usize {
-    let board = parse_board(input);
+pub(crate) fn part2(input: String) -> Result<usize, String> {
+    let board = parse_board(input)?;
     let mut solutions = vec![];
 
     let mut stack = vec![StackElem {
@@ -320,8 +308,8 @@ fn part2(input: String) -> usize {
     }
 
     solutions.sort_by(|a, b| a.0.cmp(&b.0));
-    let best_path_length = solutions[0].0;
-    solutions
+    let best_path_length = solutions.first().ok_or_else(no_path)?.0;
+    Ok(solutions
         .iter()
         .take_while(|(l, _)| *l == best_path_length)
         .fold(HashSet::from([board.goal]), |mut prev, (_, tail)| {
@@ -330,14 +318,19 @@ fn part2(input: String) -> usize {
             });
             prev
         })
-        .len()
+        .len())
 }
 
-pub fn day16(input: String) -> Day16 {
-    Day16 {
-        part1: part1(input.clone()),
-        part2: part2(input.clone()),
-    }
+/// The maze with its start and end tiles
+pub fn render_state(input: String) -> Result<impl Render, String> {
+    parse_board(input)
+}
+
+pub fn day16(input: String) -> Result<Day16, String> {
+    Ok(Day16 {
+        part1: part1(input.clone())?,
+        part2: part2(input.clone())?,
+    })
 }
 
 #[cfg(test)]
@@ -361,7 +354,7 @@ mod tests {
 #.###.#.#.#.#.#
 #S..#.....#...#
 ###############";
-        let result = day16(input.to_owned());
+        let result = day16(input.to_owned()).unwrap();
         assert_eq!(result.part1, 7036);
     }
 
@@ -382,7 +375,7 @@ mod tests {
 #.###.#.#.#.#.#
 #S..#.....#...#
 ###############";
-        let result = day16(input.to_owned());
+        let result = day16(input.to_owned()).unwrap();
         assert_eq!(result.part2, 45);
     }
 }
@@ -1,60 +1,51 @@
+use core::fmt;
+
+use crate::parse;
+
 pub struct Day17 {
     pub part1: String,
     pub part2: usize,
 }
 
 #[derive(Clone, Debug)]
-struct Computer(usize, usize, usize);
-
-fn parse_input(input: String) -> (Computer, Vec<u8>) {
-    let reg_a = str::parse::<usize>(
-        input
-            .lines()
-            .find(|line| line.starts_with("Register A:"))
-            .unwrap()
-            .split_once(":")
-            .unwrap()
-            .1
-            .trim(),
-    )
-    .unwrap();
-    let reg_b = str::parse::<usize>(
-        input
-            .lines()
-            .find(|line| line.starts_with("Register B:"))
-            .unwrap()
-            .split_once(":")
-            .unwrap()
-            .1
-            .trim(),
-    )
-    .unwrap();
-    let reg_c = str::parse::<usize>(
-        input
-            .lines()
-            .find(|line| line.starts_with("Register C:"))
-            .unwrap()
-            .split_once(":")
-            .unwrap()
-            .1
-            .trim(),
-    )
-    .unwrap();
-
-    let program = input
+pub(crate) struct Computer(usize, usize, usize);
+
+fn find_line<'a>(input: &'a str, label: &str) -> Result<&'a str, String> {
+    input
         .lines()
-        .find(|line| line.starts_with("Program:"))
-        .unwrap()
-        .split_once(":")
-        .unwrap()
-        .1
-        .trim_start()
+        .find_map(|line| line.strip_prefix(label))
+        .map(str::trim)
+        .ok_or_else(|| format!("No {:?} line", label))
+}
+
+/// The registers and a program of whole instructions, each a 3-bit opcode and
+/// operand
+pub(crate) fn parse_input(input: String) -> Result<(Computer, Vec<u8>), String> {
+    let reg_a = parse::number::<usize>(find_line(&input, "Register A:")?)?;
+    let reg_b = parse::number::<usize>(find_line(&input, "Register B:")?)?;
+    let reg_c = parse::number::<usize>(find_line(&input, "Register C:")?)?;
+
+    let program = find_line(&input, "Program:")?
         .split(",")
-        .map(|ch| str::parse::<u8>(ch).unwrap())
-        .collect::<Vec<u8>>();
+        .map(|value| match parse::number::<u8>(value)? {
+            n if n < 8 => Ok(n),
+            n => Err(format!("Invalid 3-bit value {}", n)),
+        })
+        .collect::<Result<Vec<u8>, String>>()?;
+    if program.len() % 2 != 0 {
+        return Err("Program ends part way through an instruction".to_owned());
+    }
+    if let Some(i) = (0..program.len()).step_by(2).find(|i| {
+        matches!(program[*i], OP_ADV | OP_BST | OP_OUT | OP_BDV | OP_CDV) && program[i + 1] == 7
+    }) {
+        return Err(format!(
+            "Instruction at {} uses the reserved combo operand 7",
+            i
+        ));
+    }
 
     let computer = Computer(reg_a, reg_b, reg_c);
-    (computer, program)
+    Ok((computer, program))
 }
 
 const OP_ADV: u8 = 0;
@@ -80,17 +71,21 @@ fn operand_combo(computer: &Computer, operand: u8) -> usize {
     }
 }
 
+/// A divided by 2 to the power of the combo operand
+fn divide(computer: &Computer, operand: u8) -> usize {
+    u32::try_from(operand_combo(computer, operand))
+        .ok()
+        .and_then(|shift| computer.0.checked_shr(shift))
+        .unwrap_or(0)
+}
+
 fn run_step(computer: &mut Computer, program: &Vec<u8>, i: usize) -> (usize, Option<usize>) {
     let opcode = program[i];
     let operand = program[i + 1];
 
-    let combo = operand_combo(&computer, operand);
-
     match opcode {
         OP_ADV => {
-            let numerator = computer.0;
-            let denominator = 2_u32.pow(combo as u32) as usize;
-            computer.0 = numerator / denominator;
+            computer.0 = divide(computer, operand);
             (i + 2, None)
         }
         OP_BXL => {
@@ -98,7 +93,7 @@ fn run_step(computer: &mut Computer, program: &Vec<u8>, i: usize) -> (usize, Opt
             (i + 2, None)
         }
         OP_BST => {
-            computer.1 = combo.rem_euclid(8);
+            computer.1 = operand_combo(computer, operand).rem_euclid(8);
             (i + 2, None)
         }
         OP_JNZ => {
@@ -112,28 +107,30 @@ fn run_step(computer: &mut Computer, program: &Vec<u8>, i: usize) -> (usize, Opt
             computer.1 = computer.1 ^ computer.2;
             (i + 2, None)
         }
-        OP_OUT => (i + 2, Some(combo.rem_euclid(8))),
+        OP_OUT => (i + 2, Some(operand_combo(computer, operand).rem_euclid(8))),
         OP_BDV => {
-            let numerator = computer.0;
-            let denominator = 2_u32.pow(combo as u32) as usize;
-            computer.1 = numerator / denominator;
+            computer.1 = divide(computer, operand);
             (i + 2, None)
         }
         OP_CDV => {
-            let numerator = computer.0;
-            let denominator = 2_u32.pow(combo as u32) as usize;
-            computer.2 = numerator / denominator;
+            computer.2 = divide(computer, operand);
             (i + 2, None)
         }
         _ => panic!("invalid opcode {}", opcode),
     }
 }
 
-fn run_program(computer: &mut Computer, program: &Vec<u8>) -> Vec<usize> {
+/// Beyond this, a program is assumed to loop forever
+const MAX_STEPS: usize = 1_000_000;
+
+fn run_program(computer: &mut Computer, program: &Vec<u8>) -> Result<Vec<usize>, String> {
     let mut i: usize = 0;
     let mut output = vec![];
 
-    while i < program.len() - 1 {
+    for _ in 0..MAX_STEPS {
+        if i >= program.len() - 1 {
+            return Ok(output);
+        }
         let (i_next, out) = run_step(computer, program, i);
         i = i_next;
         if let Some(o) = out {
@@ -141,20 +138,20 @@ fn run_program(computer: &mut Computer, program: &Vec<u8>) -> Vec<usize> {
         }
     }
 
-    output
+    Err(format!("Program did not halt within {} steps", MAX_STEPS))
 }
 
-fn part1(input: String) -> String {
-    let (mut computer, program) = parse_input(input);
-    run_program(&mut computer, &program)
+pub(crate) fn part1(input: String) -> Result<String, String> {
+    let (mut computer, program) = parse_input(input)?;
+    Ok(run_program(&mut computer, &program)?
         .iter()
         .map(|o| format!("{}", o))
         .collect::<Vec<String>>()
-        .join(",")
+        .join(","))
 }
 
-fn part2(input: String) -> usize {
-    let (computer, program) = parse_input(input);
+pub(crate) fn part2(input: String) -> Result<usize, String> {
+    let (computer, program) = parse_input(input)?;
     let prog_len = program.len();
 
     let mut stack = vec![(0, prog_len - 1)];
@@ -167,10 +164,10 @@ fn part2(input: String) -> usize {
             let reg_a = reg_a_init + j;
             let mut computer_mod = computer.clone();
             computer_mod.0 = reg_a;
-            let output = run_program(&mut computer_mod, &program);
+            let output = run_program(&mut computer_mod, &program)?;
             let matches = (i..prog_len)
                 .into_iter()
-                .all(|k| output[k - i] == program[k] as usize);
+                .all(|k| output.get(k - i) == Some(&(program[k] as usize)));
 
             if matches {
                 if i == 0 {
@@ -184,23 +181,122 @@ fn part2(input: String) -> usize {
                         }
                         None => Some(reg_a),
                     };
-                } else {
-                    stack.push((reg_a * 8, i - 1));
+                } else if let Some(reg_a_next) = reg_a.checked_mul(8) {
+                    stack.push((reg_a_next, i - 1));
                 }
             }
         }
     }
 
-    solution.expect("Did not find solution")
+    solution.ok_or_else(|| "No value of register A outputs the program".to_owned())
+}
+
+fn combo_name(operand: u8) -> String {
+    match operand {
+        4 => "A".to_owned(),
+        5 => "B".to_owned(),
+        6 => "C".to_owned(),
+        _ => operand.to_string(),
+    }
 }
 
-pub fn day17(input: String) -> Day17 {
-    Day17 {
-        part1: part1(input.clone()),
-        part2: part2(input.clone()),
+fn disassemble(opcode: u8, operand: u8) -> String {
+    match opcode {
+        OP_ADV => format!("adv A = A >> {}", combo_name(operand)),
+        OP_BXL => format!("bxl B = B ^ {}", operand),
+        OP_BST => format!("bst B = {} % 8", combo_name(operand)),
+        OP_JNZ => format!("jnz if A != 0 goto {}", operand),
+        OP_BXC => "bxc B = B ^ C".to_owned(),
+        OP_OUT => format!("out {} % 8", combo_name(operand)),
+        OP_BDV => format!("bdv B = A >> {}", combo_name(operand)),
+        OP_CDV => format!("cdv C = A >> {}", combo_name(operand)),
+        _ => format!("??? {} {}", opcode, operand),
     }
 }
 
+/// The registers and a listing of the program
+pub fn describe(input: String) -> Result<String, String> {
+    let (Computer(a, b, c), program) = parse_input(input)?;
+    let listing = program.chunks(2).enumerate().map(|(i, instruction)| {
+        let operand = instruction.get(1).copied().unwrap_or(0);
+        format!("  {:>3}: {}", i * 2, disassemble(instruction[0], operand))
+    });
+
+    Ok(std::iter::once(format!("A={} B={} C={}", a, b, c))
+        .chain(listing)
+        .collect::<Vec<String>>()
+        .join("\n"))
+}
+
+/// Runs the program one instruction at a time
+pub struct Debugger {
+    computer: Computer,
+    program: Vec<u8>,
+    ip: usize,
+    output: Vec<usize>,
+}
+
+impl Debugger {
+    pub fn new(input: String) -> Result<Self, String> {
+        let (computer, program) = parse_input(input)?;
+        Ok(Debugger {
+            computer,
+            program,
+            ip: 0,
+            output: vec![],
+        })
+    }
+
+    pub fn halted(&self) -> bool {
+        self.ip >= self.program.len() - 1
+    }
+
+    /// Runs the next instruction, returning false if the program has halted
+    pub fn step(&mut self) -> bool {
+        if self.halted() {
+            return false;
+        }
+        let (ip, out) = run_step(&mut self.computer, &self.program, self.ip);
+        self.ip = ip;
+        if let Some(o) = out {
+            self.output.push(o);
+        }
+        true
+    }
+}
+
+impl fmt::Display for Debugger {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Computer(a, b, c) = self.computer;
+        let next = if self.halted() {
+            "halted".to_owned()
+        } else {
+            disassemble(self.program[self.ip], self.program[self.ip + 1])
+        };
+        write!(
+            f,
+            "A={} B={} C={} ip={} next: {}\noutput: {}",
+            a,
+            b,
+            c,
+            self.ip,
+            next,
+            self.output
+                .iter()
+                .map(|o| o.to_string())
+                .collect::<Vec<String>>()
+                .join(",")
+        )
+    }
+}
+
+pub fn day17(input: String) -> Result<Day17, String> {
+    Ok(Day17 {
+        part1: part1(input.clone())?,
+        part2: part2(input.clone())?,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -212,7 +308,7 @@ Register B: 0
 Register C: 0
 
 Program: 0,1,5,4,3,0";
-        let result = part1(input.to_owned());
+        let result = part1(input.to_owned()).unwrap();
         assert_eq!(result, "4,6,3,5,6,3,5,2,1,0");
     }
 
@@ -223,7 +319,35 @@ Register B: 0
 Register C: 0
 
 Program: 0,3,5,4,3,0";
-        let result = part2(input.to_owned());
+        let result = part2(input.to_owned()).unwrap();
         assert_eq!(result, 117440);
     }
+
+    #[test]
+    fn steps_through_program() {
+        let input = r"Register A: 729
+Register B: 0
+Register C: 0
+
+Program: 0,1,5,4,3,0";
+        let mut debugger = Debugger::new(input.to_owned()).unwrap();
+        assert_eq!(
+            debugger.to_string(),
+            "A=729 B=0 C=0 ip=0 next: adv A = A >> 1\noutput: "
+        );
+        debugger.step();
+        debugger.step();
+        assert_eq!(
+            debugger.to_string(),
+            "A=364 B=0 C=0 ip=4 next: jnz if A != 0 goto 0\noutput: 4"
+        );
+        while debugger.step() {}
+        assert!(debugger
+            .to_string()
+            .ends_with("output: 4,6,3,5,6,3,5,2,1,0"));
+        assert_eq!(
+            describe(input.to_owned()).unwrap(),
+            "A=729 B=0 C=0\n    0: adv A = A >> 1\n    2: out A % 8\n    4: jnz if A != 0 goto 0"
+        );
+    }
 }
@@ -4,6 +4,10 @@ use std::{
     usize,
 };
 
+use crate::parse;
+use crate::player::Animation;
+use crate::render::{Render, Tile, BLACK, GREY, RED};
+
 pub struct Day18 {
     pub part1: usize,
     pub part2: String,
@@ -103,66 +107,112 @@ fn shortest_path(grid_size: usize, corrupted_bytes: &HashSet<usize>) -> Option<u
     None
 }
 
-fn part1(input: InputDay18) -> usize {
-    let InputDay18 {
-        grid_size, falls, ..
-    } = input;
+/// The (x, y) position of each byte, in the order they fall
+pub(crate) fn parse_bytes(
+    InputDay18 {
+        bytes, grid_size, ..
+    }: &InputDay18,
+) -> Result<Vec<(usize, usize)>, String> {
+    if *grid_size < 1 || grid_size.checked_mul(*grid_size).is_none() {
+        return Err(format!("Invalid grid size {}", grid_size));
+    }
+    parse::lines(bytes, |line| {
+        let (xs, ys) = parse::split(line, ",")?;
+        let x = parse::number::<usize>(xs)?;
+        let y = parse::number::<usize>(ys)?;
+        if x >= *grid_size || y >= *grid_size {
+            return Err(format!("Byte {},{} is outside the memory space", x, y));
+        }
+        Ok((x, y))
+    })
+}
 
-    let corrupted_bytes = input
-        .bytes
-        .lines()
-        .take(falls)
-        .fold(HashSet::new(), |mut h, line| {
-            let (xs, ys) = line.split_once(",").unwrap();
-            let x = str::parse::<usize>(xs).unwrap();
-            let y = str::parse::<usize>(ys).unwrap();
-            h.insert(y * grid_size + x);
-            h
-        });
+pub(crate) fn part1(input: InputDay18) -> Result<usize, String> {
+    let grid_size = input.grid_size;
 
-    shortest_path(grid_size, &corrupted_bytes).expect("No shortest path")
-}
+    let corrupted_bytes = parse_bytes(&input)?
+        .into_iter()
+        .take(input.falls)
+        .map(|(x, y)| y * grid_size + x)
+        .collect::<HashSet<usize>>();
 
-fn part2(input: InputDay18) -> String {
-    let InputDay18 {
-        grid_size, falls, ..
-    } = input;
+    shortest_path(grid_size, &corrupted_bytes).ok_or_else(|| "No shortest path".to_owned())
+}
 
-    let parse_byte = |line: &str| -> (usize, usize) {
-        let (xs, ys) = line.split_once(",").unwrap();
-        let x = str::parse::<usize>(xs).unwrap();
-        let y = str::parse::<usize>(ys).unwrap();
-        (x, y)
-    };
+pub(crate) fn part2(input: InputDay18) -> Result<String, String> {
+    let grid_size = input.grid_size;
+    let bytes = parse_bytes(&input)?;
 
     let mut corrupted_bytes = HashSet::new();
-    for line in input.bytes.lines().take(falls) {
-        let (x, y) = parse_byte(line);
+    for (x, y) in bytes.iter().take(input.falls) {
         corrupted_bytes.insert(y * grid_size + x);
     }
 
-    let mut remaining_bytes = input.bytes.lines().skip(falls);
-    let mut last_fallen_byte: Option<(usize, usize)> = None;
-    while let Some(fallen_byte) = remaining_bytes.next() {
-        let (x, y) = parse_byte(fallen_byte);
-        last_fallen_byte = Some((x, y));
+    for (x, y) in bytes.iter().skip(input.falls) {
         corrupted_bytes.insert(y * grid_size + x);
 
         if shortest_path(grid_size, &corrupted_bytes).is_none() {
-            break;
+            return Ok(format!("{},{}", x, y));
         }
     }
 
-    let (bx, by) = last_fallen_byte.expect("Never blocked");
+    Err("Never blocked".to_owned())
+}
+
+pub struct FallingBytes {
+    grid_size: usize,
+    bytes: Vec<(usize, usize)>,
+}
+
+struct MemorySpace<'a> {
+    animation: &'a FallingBytes,
+    fallen: HashSet<(usize, usize)>,
+    last: Option<(usize, usize)>,
+}
+
+impl Render for MemorySpace<'_> {
+    fn size(&self) -> (usize, usize) {
+        (self.animation.grid_size, self.animation.grid_size)
+    }
 
-    format!("{},{}", bx, by)
+    fn tile(&self, x: usize, y: usize) -> Tile {
+        if self.last == Some((x, y)) {
+            Tile::new('#', RED)
+        } else if self.fallen.contains(&(x, y)) {
+            Tile::new('#', GREY)
+        } else {
+            Tile::new('.', BLACK)
+        }
+    }
 }
 
-pub fn day18(input: InputDay18) -> Day18 {
-    Day18 {
-        part1: part1(input.clone()),
-        part2: part2(input.clone()),
+impl Animation for FallingBytes {
+    fn len(&self) -> usize {
+        self.bytes.len() + 1
     }
+
+    fn frame(&self, step: usize) -> Box<dyn Render + '_> {
+        Box::new(MemorySpace {
+            animation: self,
+            fallen: self.bytes[..step].iter().copied().collect(),
+            last: step.checked_sub(1).map(|i| self.bytes[i]),
+        })
+    }
+}
+
+/// The bytes falling into memory space one at a time
+pub fn animation(input: InputDay18) -> Result<FallingBytes, String> {
+    Ok(FallingBytes {
+        grid_size: input.grid_size,
+        bytes: parse_bytes(&input)?,
+    })
+}
+
+pub fn day18(input: InputDay18) -> Result<Day18, String> {
+    Ok(Day18 {
+        part1: part1(input.clone())?,
+        part2: part2(input.clone())?,
+    })
 }
 
 #[cfg(test)]
@@ -201,7 +251,7 @@ mod tests {
             grid_size: 7,
             falls: 12,
         };
-        let result = part1(input);
+        let result = part1(input).unwrap();
         assert_eq!(result, 22);
     }
 
@@ -237,7 +287,7 @@ mod tests {
             grid_size: 7,
             falls: 12,
         };
-        let result = part2(input);
+        let result = part2(input).unwrap();
         assert_eq!(result, "6,1");
     }
 }
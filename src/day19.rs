@@ -3,6 +3,8 @@ use std::{
     collections::{BinaryHeap, HashMap, HashSet},
 };
 
+use crate::parse;
+
 pub struct Day19 {
     pub part1: usize,
     pub part2: usize,
@@ -25,12 +27,36 @@ fn parse_color(ch: &str) -> Option<i8> {
     }
 }
 
-fn parse_color_array(line: String) -> Vec<i8> {
+fn parse_color_array(line: &str) -> Result<Vec<i8>, String> {
+    if line.is_empty() {
+        return Err("Expected at least one stripe".to_owned());
+    }
     line.split("")
-        .map(parse_color)
-        .filter(|c| c.is_some())
-        .map(|c| c.unwrap())
-        .collect::<Vec<i8>>()
+        .filter(|ch| !ch.is_empty())
+        .map(|ch| parse_color(ch).ok_or_else(|| format!("Invalid color {:?}", ch)))
+        .collect()
+}
+
+type Patterns = Vec<Vec<i8>>;
+
+/// The towels from the first line, and a design on each line after the
+/// blank one
+pub(crate) fn parse_input(input: &str) -> Result<(Patterns, Patterns), String> {
+    let towels = input.lines().next().ok_or_else(|| "No towels".to_owned())?;
+    let sections = parse::on_line(
+        0,
+        towels
+            .split(", ")
+            .map(parse_color_array)
+            .collect::<Result<Patterns, String>>(),
+    )?;
+    let patterns = input
+        .lines()
+        .enumerate()
+        .skip(2)
+        .map(|(i, line)| parse::on_line(i, parse_color_array(line)))
+        .collect::<Result<Patterns, String>>()?;
+    Ok((sections, patterns))
 }
 
 fn matches_start(pattern: &Vec<i8>, section: &Vec<i8>, offset: usize) -> bool {
@@ -95,71 +121,81 @@ fn get_num_possible_patterns_from_offset(
     sections: &Vec<Vec<i8>>,
     offset: usize,
     cache: &mut HashMap<usize, usize>,
-) -> usize {
-    let mut result = 0;
+) -> Option<usize> {
+    let mut result: usize = 0;
     for p in sections
         .iter()
         .filter(|p| matches_start(pattern, p, offset))
     {
         let offset_next = offset + p.len();
-        if offset_next == pattern.len() {
-            result += 1
+        let r = if offset_next == pattern.len() {
+            1
         } else if let Some(r) = cache.get(&offset_next) {
-            result += r;
+            *r
         } else {
-            let r = get_num_possible_patterns_from_offset(pattern, sections, offset_next, cache);
+            let r = get_num_possible_patterns_from_offset(pattern, sections, offset_next, cache)?;
             cache.insert(offset_next, r);
-            result += r;
-        }
+            r
+        };
+        result = result.checked_add(r)?;
     }
 
-    result
+    Some(result)
 }
 
-fn get_num_possible_patterns(pattern: &Vec<i8>, sections: &Vec<Vec<i8>>) -> usize {
+/// The number of arrangements of towels making the pattern, or None if there
+/// are too many to count
+fn get_num_possible_patterns(pattern: &Vec<i8>, sections: &Vec<Vec<i8>>) -> Option<usize> {
     let mut cache = HashMap::new();
     get_num_possible_patterns_from_offset(pattern, sections, 0, &mut cache)
 }
 
-fn part1(input: String) -> usize {
-    let sections = input
-        .lines()
-        .next()
-        .unwrap()
-        .split(", ")
-        .map(|line| parse_color_array(line.to_owned()))
-        .collect::<Vec<Vec<i8>>>();
+pub(crate) fn part1(input: String) -> Result<usize, String> {
+    let (sections, patterns) = parse_input(&input)?;
 
-    input
-        .lines()
-        .skip(2)
-        .map(|line| parse_color_array(line.to_owned()))
+    Ok(patterns
+        .iter()
         .filter(|pattern| construct_pattern(pattern, &sections))
-        .count()
+        .count())
+}
+
+pub(crate) fn part2(input: String) -> Result<usize, String> {
+    let (sections, patterns) = parse_input(&input)?;
+
+    patterns.iter().try_fold(0_usize, |sum, pattern| {
+        get_num_possible_patterns(pattern, &sections)
+            .and_then(|n| sum.checked_add(n))
+            .ok_or_else(|| "Too many arrangements to count".to_owned())
+    })
 }
 
-fn part2(input: String) -> usize {
-    let sections = input
+/// The available towels and the designs to make from them
+pub fn describe(input: String) -> Result<String, String> {
+    parse_input(&input)?;
+    let towels = input
         .lines()
         .next()
-        .unwrap()
+        .unwrap_or_default()
         .split(", ")
-        .map(|line| parse_color_array(line.to_owned()))
-        .collect::<Vec<Vec<i8>>>();
-
-    input
-        .lines()
-        .skip(2)
-        .map(|line| parse_color_array(line.to_owned()))
-        .map(|pattern| get_num_possible_patterns(&pattern, &sections))
-        .fold(0, |sum, n| sum + n)
+        .collect::<Vec<&str>>();
+    let designs = input.lines().skip(2).collect::<Vec<&str>>();
+    let longest = towels.iter().map(|t| t.len()).max().unwrap_or(0);
+
+    Ok(format!(
+        "{} towels (longest {}): {}\n{} designs:\n  {}",
+        towels.len(),
+        longest,
+        towels.join(", "),
+        designs.len(),
+        designs.join("\n  ")
+    ))
 }
 
-pub fn day19(input: String) -> Day19 {
-    Day19 {
-        part1: part1(input.clone()),
-        part2: part2(input.clone()),
-    }
+pub fn day19(input: String) -> Result<Day19, String> {
+    Ok(Day19 {
+        part1: part1(input.clone())?,
+        part2: part2(input.clone())?,
+    })
 }
 
 #[cfg(test)]
@@ -178,7 +214,7 @@ ubwu
 bwurrg
 brgr
 bbrgwb";
-        let result = part1(input.to_owned());
+        let result = part1(input.to_owned()).unwrap();
         assert_eq!(result, 6);
     }
 
@@ -194,7 +230,7 @@ ubwu
 bwurrg
 brgr
 bbrgwb";
-        let result = part2(input.to_owned());
+        let result = part2(input.to_owned()).unwrap();
         assert_eq!(result, 16);
     }
 }
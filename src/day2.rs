@@ -1,85 +1,256 @@
+use crate::explain::Explanation;
+use crate::parse;
+
 pub struct Day2 {
     pub num_safe: i32,
     pub num_safe_with_dampener: i32,
+    /// For each report, the indices of the levels the dampener removed to
+    /// make it safe, or None if it couldn't
+    pub removed: Vec<Option<Vec<usize>>>,
 }
 
-fn get_num_safe(input: String, with_dampener: bool) -> i32 {
-    input.lines().fold(0, |sum, line| {
-        let is_safe = |values: Vec<i8>| -> bool {
-            let first = values[0];
-            let direction = if values[values.len() - 1] > first {
-                1
-            } else {
-                -1
-            };
+/// What makes a report safe: every step between levels changes by between
+/// `min_step` and `max_step`, and if `monotonic`, always the same way.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rules {
+    pub min_step: i64,
+    pub max_step: i64,
+    pub monotonic: bool,
+}
 
-            let is_safe = values
-                .iter()
-                .skip(1)
-                .fold((true, first), |prev, next| {
-                    if !prev.0 {
-                        return (false, 0);
-                    }
-                    let diff = (next - prev.1) * direction;
-                    if diff < 1 || diff > 3 {
-                        return (false, 0);
-                    }
-                    (true, *next)
-                })
-                .0;
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            min_step: 1,
+            max_step: 3,
+            monotonic: true,
+        }
+    }
+}
 
-            is_safe
-        };
+#[derive(Clone, Debug)]
+pub struct Day2Input {
+    pub reports: String,
+    /// Levels the Problem Dampener may remove from each report
+    pub dampener: usize,
+    pub rules: Rules,
+}
 
-        let values = line
-            .split(" ")
-            .map(|c| str::parse::<i8>(c).unwrap())
-            .collect::<Vec<i8>>();
+/// The first pair of levels in an unsafe report which breaks the rules
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    /// The (0-based) index of the report and of the levels in it
+    pub report: usize,
+    pub levels: (usize, usize),
+    pub reason: String,
+}
 
-        if is_safe(values.clone()) {
-            return sum + 1;
+impl Rules {
+    fn validate(&self) -> Result<(), String> {
+        if self.min_step < 0 || self.min_step > self.max_step {
+            return Err(format!(
+                "Invalid steps {} to {}",
+                self.min_step, self.max_step
+            ));
         }
+        Ok(())
+    }
 
-        if !with_dampener {
-            return sum;
-        }
+    /// Whether `from -> to` is an allowed step, going up if `direction` is 1
+    /// or down if -1
+    fn allows(&self, from: i64, to: i64, direction: i128) -> bool {
+        let diff = to as i128 - from as i128;
+        let step = if self.monotonic {
+            diff * direction
+        } else {
+            diff.abs()
+        };
+        (self.min_step as i128..=self.max_step as i128).contains(&step)
+    }
 
-        let is_safe_with_idx_removed =
-            values.iter().enumerate().fold((false, 0), |prev, (i, _)| {
-                if prev.0 {
-                    return prev;
-                }
-
-                let values_with_idx_removed = values
-                    .iter()
-                    .enumerate()
-                    .filter(|(j, _)| *j != i)
-                    .map(|(_, v)| *v)
-                    .collect::<Vec<i8>>();
-
-                if is_safe(values_with_idx_removed) {
-                    (true, i)
+    /// Checks a report, returning the index of the first level which breaks
+    /// the rules along with the reason. The first change sets the direction.
+    fn check(&self, values: &[i64]) -> Result<(), (usize, String)> {
+        let mut direction = 0;
+        for (i, pair) in values.windows(2).enumerate() {
+            let (prev, next) = (pair[0], pair[1]);
+            let diff = next as i128 - prev as i128;
+            if direction == 0 {
+                direction = diff.signum();
+            }
+            let turned = diff * direction < 0;
+            let stalled = direction != 0 && diff == 0 && self.min_step > 0;
+            let reason = if self.monotonic && (turned || stalled) {
+                let way = if direction > 0 {
+                    "increasing"
                 } else {
-                    prev
-                }
-            });
+                    "decreasing"
+                };
+                format!("{} -> {} is not {}", prev, next, way)
+            } else if diff.abs() < self.min_step as i128 {
+                format!(
+                    "{} -> {} changes by less than {}",
+                    prev, next, self.min_step
+                )
+            } else if diff.abs() > self.max_step as i128 {
+                format!(
+                    "{} -> {} changes by more than {}",
+                    prev, next, self.max_step
+                )
+            } else {
+                continue;
+            };
+            return Err((i + 1, reason));
+        }
+        Ok(())
+    }
+}
 
-        if is_safe_with_idx_removed.0 {
-            sum + 1
-        } else {
-            sum
+fn parse_report(line: &str) -> Result<Vec<i64>, String> {
+    let values = line
+        .split(" ")
+        .map(parse::number::<i64>)
+        .collect::<Result<Vec<i64>, String>>()?;
+    if values.is_empty() {
+        return Err("Empty report".to_owned());
+    }
+    Ok(values)
+}
+
+pub(crate) fn parse_input(input: &str) -> Result<Vec<Vec<i64>>, String> {
+    parse::lines(input, parse_report)
+}
+
+/// The fewest levels to remove to make the report safe in one direction, if
+/// no more than `max`. A report is safe when the levels kept form a chain of
+/// allowed steps, so this finds the chain ending at each level with the fewest
+/// levels dropped, looking back at most `max + 1` levels for the one before.
+fn removals_in_direction(
+    values: &[i64],
+    max: usize,
+    rules: &Rules,
+    direction: i128,
+) -> Option<Vec<usize>> {
+    let n = values.len();
+    // levels removed so far when keeping level i, and the level kept before it
+    let mut chains: Vec<(usize, Option<usize>)> = Vec::with_capacity(n);
+    for i in 0..n {
+        let mut chain = (i, None);
+        for j in i.saturating_sub(max.saturating_add(1))..i {
+            let removed = chains[j].0 + (i - j - 1);
+            // ties keep the later level, so the earlier ones are removed
+            if rules.allows(values[j], values[i], direction) && removed <= chain.0 {
+                chain = (removed, Some(j));
+            }
         }
-    })
+        chains.push(chain);
+    }
+
+    let (last, removed) = (0..n)
+        .rev()
+        .map(|i| (i, chains[i].0 + (n - 1 - i)))
+        .min_by_key(|(_, removed)| *removed)?;
+    if removed > max {
+        return None;
+    }
+
+    let mut kept = vec![false; n];
+    let mut level = Some(last);
+    while let Some(i) = level {
+        kept[i] = true;
+        level = chains[i].1;
+    }
+    Some((0..n).filter(|i| !kept[*i]).collect())
 }
 
-pub fn day2(input: String) -> Day2 {
-    let num_safe = get_num_safe(input.to_owned(), false);
-    let num_safe_with_dampener = get_num_safe(input.to_owned(), true);
+/// The indices of the fewest levels which the dampener can remove to make the
+/// report safe, removing at most `max`.
+fn removals(values: &[i64], max: usize, rules: &Rules) -> Option<Vec<usize>> {
+    let directions: &[i128] = if rules.monotonic { &[1, -1] } else { &[1] };
+    directions
+        .iter()
+        .filter_map(|direction| removals_in_direction(values, max, rules, *direction))
+        .min_by_key(|removed| removed.len())
+}
+
+pub fn day2(input: Day2Input) -> Result<Day2, String> {
+    input.rules.validate()?;
+    let reports = parse_input(&input.reports)?;
+    let num_safe = reports
+        .iter()
+        .filter(|values| input.rules.check(values).is_ok())
+        .count() as i32;
+    let removed = reports
+        .iter()
+        .map(|values| removals(values, input.dampener, &input.rules))
+        .collect::<Vec<Option<Vec<usize>>>>();
+    let num_safe_with_dampener = removed.iter().filter(|r| r.is_some()).count() as i32;
 
-    Day2 {
+    Ok(Day2 {
         num_safe,
         num_safe_with_dampener,
-    }
+        removed,
+    })
+}
+
+/// Why each unsafe report is unsafe, before the dampener
+pub fn diagnose(input: &Day2Input) -> Result<Vec<Diagnostic>, String> {
+    input.rules.validate()?;
+    Ok(parse_input(&input.reports)?
+        .iter()
+        .enumerate()
+        .filter_map(|(report, values)| {
+            let (i, reason) = input.rules.check(values).err()?;
+            Some(Diagnostic {
+                report,
+                levels: (i - 1, i),
+                reason,
+            })
+        })
+        .collect())
+}
+
+pub fn explain(input: Day2Input) -> Result<Vec<Explanation>, String> {
+    let reports = parse_input(&input.reports)?;
+    let lines = input.reports.lines().collect::<Vec<&str>>();
+    Ok(diagnose(&input)?
+        .into_iter()
+        .flat_map(
+            |Diagnostic {
+                 report,
+                 levels,
+                 reason,
+             }| {
+                let values = &reports[report];
+                let subject = format!("line {} ({})", report + 1, lines[report]);
+                let level = |j: &usize| format!("{} ({})", j + 1, values[*j]);
+                let dampened = match removals(values, input.dampener, &input.rules) {
+                    Some(removed) if removed.len() == 1 => {
+                        format!("safe after removing level {}", level(&removed[0]))
+                    }
+                    Some(removed) => format!(
+                        "safe after removing levels {}",
+                        removed
+                            .iter()
+                            .map(level)
+                            .collect::<Vec<String>>()
+                            .join(", ")
+                    ),
+                    None if input.dampener == 0 => "unsafe with no levels removed".to_owned(),
+                    None if input.dampener == 1 => "unsafe whichever level is removed".to_owned(),
+                    None => format!("unsafe even after removing up to {} levels", input.dampener),
+                };
+                vec![
+                    Explanation::new(
+                        1,
+                        subject.clone(),
+                        format!("unsafe at level {}: {}", levels.1 + 1, reason),
+                    ),
+                    Explanation::new(2, subject, dampened),
+                ]
+            },
+        )
+        .collect())
 }
 
 #[cfg(test)]
@@ -96,7 +267,13 @@ mod tests {
 1 3 6 7 9
 ";
 
-        assert_eq!(day2(example.to_owned()).num_safe, 2);
+        let input = Day2Input {
+            reports: example.to_owned(),
+            dampener: 1,
+            rules: Rules::default(),
+        };
+
+        assert_eq!(day2(input).unwrap().num_safe, 2);
     }
 
     #[test]
@@ -109,6 +286,112 @@ mod tests {
 1 3 6 7 9
 ";
 
-        assert_eq!(day2(example.to_owned()).num_safe_with_dampener, 4);
+        let input = Day2Input {
+            reports: example.to_owned(),
+            dampener: 1,
+            rules: Rules::default(),
+        };
+
+        assert_eq!(day2(input).unwrap().num_safe_with_dampener, 4);
+    }
+
+    #[test]
+    fn removes_up_to_k_levels() {
+        let example = r"1 9 2 8 3 4
+7 6 4 2 1
+1 5 9 13 17
+";
+        let input = Day2Input {
+            reports: example.to_owned(),
+            dampener: 2,
+            rules: Rules::default(),
+        };
+
+        let result = day2(input).unwrap();
+
+        assert_eq!(result.num_safe_with_dampener, 2);
+        assert_eq!(result.removed, vec![Some(vec![1, 3]), Some(vec![]), None]);
+    }
+
+    #[test]
+    fn explains_unsafe_reports() {
+        let example = r"7 6 4 2 1
+1 2 7 8 9
+1 3 2 4 5
+";
+
+        let input = Day2Input {
+            reports: example.to_owned(),
+            dampener: 1,
+            rules: Rules::default(),
+        };
+
+        let explanations = explain(input).unwrap();
+
+        assert_eq!(
+            explanations,
+            vec![
+                Explanation::new(
+                    1,
+                    "line 2 (1 2 7 8 9)",
+                    "unsafe at level 3: 2 -> 7 changes by more than 3"
+                ),
+                Explanation::new(2, "line 2 (1 2 7 8 9)", "unsafe whichever level is removed"),
+                Explanation::new(
+                    1,
+                    "line 3 (1 3 2 4 5)",
+                    "unsafe at level 3: 3 -> 2 is not increasing"
+                ),
+                Explanation::new(2, "line 3 (1 3 2 4 5)", "safe after removing level 2 (3)"),
+            ]
+        );
+
+        let dampened = |dampener| {
+            let input = Day2Input {
+                reports: "1 9 2 8 3 7 4\n".to_owned(),
+                dampener,
+                rules: Rules::default(),
+            };
+            explain(input).unwrap().pop().unwrap().detail
+        };
+        assert_eq!(dampened(0), "unsafe with no levels removed");
+        assert_eq!(dampened(2), "unsafe even after removing up to 2 levels");
+    }
+
+    #[test]
+    fn diagnoses_with_custom_rules() {
+        let example = r"10 5 10 5
+1 2 2 3
+3000000000 3000000010
+";
+        let input = Day2Input {
+            reports: example.to_owned(),
+            dampener: 1,
+            rules: Rules {
+                min_step: 1,
+                max_step: 5,
+                monotonic: false,
+            },
+        };
+
+        let result = day2(input.clone()).unwrap();
+        let diagnostics = diagnose(&input).unwrap();
+
+        assert_eq!(result.num_safe, 1);
+        assert_eq!(
+            diagnostics,
+            vec![
+                Diagnostic {
+                    report: 1,
+                    levels: (1, 2),
+                    reason: "2 -> 2 changes by less than 1".to_owned(),
+                },
+                Diagnostic {
+                    report: 2,
+                    levels: (0, 1),
+                    reason: "3000000000 -> 3000000010 changes by more than 5".to_owned(),
+                },
+            ]
+        );
     }
 }
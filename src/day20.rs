@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use crate::parse;
+
 pub struct Day20 {
     pub part1: usize,
     pub part2: usize,
@@ -45,31 +47,30 @@ fn options_from_cell(width: usize, height: usize, i: usize) -> Vec<usize> {
     .collect::<Vec<usize>>()
 }
 
-fn parse_maze(maze: String) -> (usize, usize, Vec<i8>, Vec<usize>) {
-    let width = maze.lines().next().unwrap().len();
-    let height = maze.lines().count();
+pub(crate) fn parse_maze(maze: String) -> Result<(usize, usize, Vec<i8>, Vec<usize>), String> {
+    let grid = parse::grid(&maze)?;
+    parse::only_chars(&grid, "#.SE")?;
+    let (sx, sy) = parse::find_one(&grid, 'S')?;
+    parse::find_one(&grid, 'E')?;
+
+    let width = grid[0].len();
+    let height = grid.len();
 
-    let cells = maze
-        .lines()
-        .flat_map(|line| {
-            line.chars()
+    let cells = grid
+        .iter()
+        .flat_map(|row| {
+            row.iter()
                 .map(|ch| match ch {
                     '#' => WALL,
                     '.' => TRACK,
                     'S' => START,
-                    'E' => END,
-                    _ => panic!("Invalid character {}", ch),
+                    _ => END,
                 })
                 .collect::<Vec<i8>>()
         })
         .collect::<Vec<i8>>();
 
-    let start = cells
-        .iter()
-        .enumerate()
-        .find(|(_, cell)| **cell == START)
-        .expect("Could not find start")
-        .0;
+    let start = sy * width + sx;
 
     let track_crawled = false;
     let mut track = vec![start];
@@ -84,8 +85,8 @@ fn parse_maze(maze: String) -> (usize, usize, Vec<i8>, Vec<usize>) {
             })
             .collect::<Vec<&usize>>();
 
-        if options.len() != 1 {
-            panic!("Could not crawl track");
+        if options.len() != 1 || track.len() > cells.len() {
+            return Err("Could not crawl track".to_owned());
         } else {
             track.push(options[0].clone());
             prev = Some(i);
@@ -96,22 +97,22 @@ fn parse_maze(maze: String) -> (usize, usize, Vec<i8>, Vec<usize>) {
         }
     }
 
-    (width, height, cells, track)
+    Ok((width, height, cells, track))
 }
 
-fn part1(
+pub(crate) fn part1(
     Day20Input {
         maze,
         cheat_threshold,
     }: Day20Input,
-) -> usize {
-    let (width, height, cells, track) = parse_maze(maze);
+) -> Result<usize, String> {
+    let (width, height, cells, track) = parse_maze(maze)?;
 
     let length_base = track.len() - 1;
 
     let mut num_cheats = 0;
     let mut c = 0;
-    while c < length_base - 2 {
+    while c + 2 < length_base {
         let track_rest = &track[c + 3..];
 
         options_from_cell(width, height, track[c])
@@ -136,31 +137,30 @@ fn part1(
         c += 1;
     }
 
-    num_cheats
+    Ok(num_cheats)
 }
 
 const MAX_CHEAT: usize = 20;
 
-fn part2(
+pub(crate) fn part2(
     Day20Input {
         maze,
         cheat_threshold,
     }: Day20Input,
-) -> usize {
-    let (width, _height, _cells, track) = parse_maze(maze);
+) -> Result<usize, String> {
+    let (width, _height, _cells, track) = parse_maze(maze)?;
 
     let length_base = track.len() - 1;
 
     let mut cheat_count = HashMap::new();
     let mut c = 0;
-    while c < length_base - 1 - cheat_threshold {
+    while c + 1 + cheat_threshold < length_base {
         let track_rest = &track[c + cheat_threshold..];
 
         let x0 = track[c].rem_euclid(width);
         let y0 = track[c] / width;
 
         track_rest.iter().enumerate().for_each(|(d, dest)| {
-            print!("\rc={}, d={}", c, d);
             let x1 = dest.rem_euclid(width);
             let y1 = dest / width;
             let manhattan_distance = x1.abs_diff(x0) + y1.abs_diff(y0);
@@ -178,14 +178,14 @@ fn part2(
         c += 1;
     }
 
-    cheat_count.iter().fold(0, |sum, (_, count)| sum + count)
+    Ok(cheat_count.iter().fold(0, |sum, (_, count)| sum + count))
 }
 
-pub fn day20(input: Day20Input) -> Day20 {
-    Day20 {
-        part1: part1(input.clone()),
-        part2: part2(input.clone()),
-    }
+pub fn day20(input: Day20Input) -> Result<Day20, String> {
+    Ok(Day20 {
+        part1: part1(input.clone())?,
+        part2: part2(input.clone())?,
+    })
 }
 
 #[cfg(test)]
@@ -213,84 +213,96 @@ mod tests {
             part1(Day20Input {
                 maze: input.to_owned(),
                 cheat_threshold: 0
-            }),
+            })
+            .unwrap(),
             44
         );
         assert_eq!(
             part1(Day20Input {
                 maze: input.to_owned(),
                 cheat_threshold: 1
-            }),
+            })
+            .unwrap(),
             44
         );
         assert_eq!(
             part1(Day20Input {
                 maze: input.to_owned(),
                 cheat_threshold: 2
-            }),
+            })
+            .unwrap(),
             44
         );
         assert_eq!(
             part1(Day20Input {
                 maze: input.to_owned(),
                 cheat_threshold: 3
-            }),
+            })
+            .unwrap(),
             30
         );
         assert_eq!(
             part1(Day20Input {
                 maze: input.to_owned(),
                 cheat_threshold: 4
-            }),
+            })
+            .unwrap(),
             30
         );
         assert_eq!(
             part1(Day20Input {
                 maze: input.to_owned(),
                 cheat_threshold: 5
-            }),
+            })
+            .unwrap(),
             16
         );
         assert_eq!(
             part1(Day20Input {
                 maze: input.to_owned(),
                 cheat_threshold: 6
-            }),
+            })
+            .unwrap(),
             16
         );
         assert_eq!(
             part1(Day20Input {
                 maze: input.to_owned(),
                 cheat_threshold: 7
-            }),
+            })
+            .unwrap(),
             14
         );
         assert_eq!(
             part1(Day20Input {
                 maze: input.to_owned(),
                 cheat_threshold: 8
-            }),
+            })
+            .unwrap(),
             14
         );
         assert_eq!(
             part1(Day20Input {
                 maze: input.to_owned(),
                 cheat_threshold: 9
-            }),
+            })
+            .unwrap(),
             10
         );
         assert_eq!(
             part1(Day20Input {
                 maze: input.to_owned(),
                 cheat_threshold: 10
-            }),
+            })
+            .unwrap(),
             10
         );
         assert_eq!(
             part1(Day20Input {
                 maze: input.to_owned(),
                 cheat_threshold: 11
-            }),
+            })
+            .unwrap(),
             8
         );
     }
@@ -316,21 +328,24 @@ mod tests {
             part2(Day20Input {
                 maze: input.to_owned(),
                 cheat_threshold: 50
-            }),
+            })
+            .unwrap(),
             32 + 31 + 29 + 39 + 25 + 23 + 20 + 19 + 12 + 14 + 12 + 22 + 4 + 3
         );
         assert_eq!(
             part2(Day20Input {
                 maze: input.to_owned(),
                 cheat_threshold: 52
-            }),
+            })
+            .unwrap(),
             31 + 29 + 39 + 25 + 23 + 20 + 19 + 12 + 14 + 12 + 22 + 4 + 3
         );
         assert_eq!(
             part2(Day20Input {
                 maze: input.to_owned(),
                 cheat_threshold: 54
-            }),
+            })
+            .unwrap(),
             29 + 39 + 25 + 23 + 20 + 19 + 12 + 14 + 12 + 22 + 4 + 3
         );
     }
@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+
+use crate::parse;
+
+pub struct Day21 {
+    pub part1: usize,
+    pub part2: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct Day21Input {
+    pub codes: String,
+    /// Robots at directional keypads in part 2; part 1 always has two
+    pub robots: usize,
+}
+
+const NUMERIC: [&str; 4] = ["789", "456", "123", " 0A"];
+const DIRECTIONAL: [&str; 2] = [" ^A", "<v>"];
+
+fn position(keypad: &[&str], key: char) -> (usize, usize) {
+    keypad
+        .iter()
+        .enumerate()
+        .find_map(|(y, row)| row.chars().position(|ch| ch == key).map(|x| (x, y)))
+        .unwrap_or_else(|| panic!("No key {} on keypad", key))
+}
+
+/// The ways of moving from one key to another and pressing it: all the
+/// horizontal moves then all the vertical ones, or the other way round,
+/// without passing over the gap. Zig-zagging never helps, as every turn is
+/// an extra trip for the robot further up the chain.
+fn paths(keypad: &[&str], from: char, to: char) -> Vec<String> {
+    let (x0, y0) = position(keypad, from);
+    let (x1, y1) = position(keypad, to);
+    let gap = position(keypad, ' ');
+
+    let horizontal = if x1 > x0 { ">" } else { "<" }.repeat(x1.abs_diff(x0));
+    let vertical = if y1 > y0 { "v" } else { "^" }.repeat(y1.abs_diff(y0));
+
+    let mut paths = vec![];
+    if (x1, y0) != gap {
+        paths.push(format!("{}{}A", horizontal, vertical));
+    }
+    if (x0, y1) != gap {
+        paths.push(format!("{}{}A", vertical, horizontal));
+    }
+    paths.dedup();
+    paths
+}
+
+type Cache = HashMap<(char, char, usize), usize>;
+
+/// Presses needed to type `sequence` on a directional keypad with `robots`
+/// more robots between it and the human, starting from A. Costs too large to
+/// count saturate at `usize::MAX`.
+fn sequence_cost(sequence: &str, robots: usize, cache: &mut Cache) -> usize {
+    std::iter::once('A')
+        .chain(sequence.chars())
+        .zip(sequence.chars())
+        .map(|(from, to)| press_cost(from, to, robots, cache))
+        .fold(0, usize::saturating_add)
+}
+
+fn press_cost(from: char, to: char, robots: usize, cache: &mut Cache) -> usize {
+    if robots == 0 {
+        return 1;
+    }
+    if let Some(cost) = cache.get(&(from, to, robots)) {
+        return *cost;
+    }
+
+    let cost = paths(&DIRECTIONAL, from, to)
+        .iter()
+        .map(|path| sequence_cost(path, robots - 1, cache))
+        .min()
+        .unwrap_or(0);
+    cache.insert((from, to, robots), cost);
+    cost
+}
+
+/// Presses the human needs to make for the robot at the numeric keypad to
+/// type `code`, through `robots` robots at directional keypads.
+fn code_cost(code: &str, robots: usize, cache: &mut Cache) -> usize {
+    std::iter::once('A')
+        .chain(code.chars())
+        .zip(code.chars())
+        .map(|(from, to)| {
+            paths(&NUMERIC, from, to)
+                .iter()
+                .map(|path| sequence_cost(path, robots, cache))
+                .min()
+                .unwrap_or(0)
+        })
+        .fold(0, usize::saturating_add)
+}
+
+/// Each code with its numeric part, which is digits ending in A
+pub(crate) fn parse_input(input: &str) -> Result<Vec<(&str, usize)>, String> {
+    parse::lines(input, |line| {
+        let digits = line
+            .strip_suffix('A')
+            .ok_or_else(|| format!("Expected A at the end of {:?}", line))?;
+        if digits.is_empty() || !digits.chars().all(|ch| ch.is_ascii_digit()) {
+            return Err(format!("Invalid code {:?}", line));
+        }
+        Ok((line, parse::number::<usize>(digits)?))
+    })
+}
+
+fn complexity(input: &str, robots: usize) -> Result<usize, String> {
+    let mut cache = HashMap::new();
+    parse_input(input)?
+        .iter()
+        .try_fold(0_usize, |sum, (code, value)| {
+            Some(code_cost(code, robots, &mut cache))
+                .filter(|cost| *cost != usize::MAX)
+                .and_then(|cost| cost.checked_mul(*value))
+                .and_then(|complexity| sum.checked_add(complexity))
+                .ok_or_else(|| "Complexity is too large".to_owned())
+        })
+}
+
+pub(crate) fn part1(input: Day21Input) -> Result<usize, String> {
+    complexity(&input.codes, 2)
+}
+
+pub(crate) fn part2(input: Day21Input) -> Result<usize, String> {
+    complexity(&input.codes, input.robots)
+}
+
+pub fn day21(input: Day21Input) -> Result<Day21, String> {
+    Ok(Day21 {
+        part1: part1(input.clone())?,
+        part2: part2(input.clone())?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gets_part1() {
+        let input = r"029A
+980A
+179A
+456A
+379A";
+
+        let result = part1(Day21Input {
+            codes: input.to_owned(),
+            robots: 25,
+        })
+        .unwrap();
+
+        assert_eq!(result, 126384);
+    }
+
+    #[test]
+    fn gets_part2() {
+        let input = r"029A
+980A
+179A
+456A
+379A";
+
+        let result = day21(Day21Input {
+            codes: input.to_owned(),
+            robots: 25,
+        })
+        .unwrap();
+
+        assert_eq!(result.part2, 154115708116294);
+    }
+
+    #[test]
+    fn chains_any_number_of_robots() {
+        let input = |robots| Day21Input {
+            codes: "029A".to_owned(),
+            robots,
+        };
+
+        // with no robots in between the human types <A^A>^^AvvvA directly
+        assert_eq!(part2(input(0)), Ok(12 * 29));
+        assert_eq!(part2(input(1)), Ok(28 * 29));
+        assert_eq!(part2(input(2)), part1(input(2)));
+        assert_eq!(part2(input(100)), Err("Complexity is too large".to_owned()));
+    }
+}
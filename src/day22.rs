@@ -0,0 +1,150 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::parse;
+
+pub struct Day22 {
+    pub part1: u64,
+    pub part2: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct Day22Input {
+    pub buyers: String,
+    /// New secret numbers each buyer generates
+    pub iterations: usize,
+    /// Price changes the monkey watches for before selling
+    pub sequence_length: usize,
+}
+
+const PRUNE: u64 = 16777216;
+
+/// Price changes run from -9 to 9
+const NUM_CHANGES: usize = 19;
+
+fn next_secret(secret: u64) -> u64 {
+    let secret = ((secret * 64) ^ secret) % PRUNE;
+    let secret = ((secret / 32) ^ secret) % PRUNE;
+    ((secret * 2048) ^ secret) % PRUNE
+}
+
+/// The initial secret number of each buyer
+pub(crate) fn parse_input(input: &str) -> Result<Vec<u64>, String> {
+    parse::lines(input, |line| match parse::number::<u64>(line)? {
+        n if n < PRUNE => Ok(n),
+        n => Err(format!("Secret number {} is out of range", n)),
+    })
+}
+
+pub(crate) fn part1(input: Day22Input) -> Result<u64, String> {
+    Ok(parse_input(&input.buyers)?
+        .into_iter()
+        .map(|secret| (0..input.iterations).fold(secret, |s, _| next_secret(s)))
+        .sum())
+}
+
+pub(crate) fn part2(
+    Day22Input {
+        buyers,
+        iterations,
+        sequence_length,
+    }: Day22Input,
+) -> Result<usize, String> {
+    // each run of changes is packed into one number, base 19
+    let modulus = u32::try_from(sequence_length)
+        .ok()
+        .and_then(|length| NUM_CHANGES.checked_pow(length))
+        .filter(|_| sequence_length > 0)
+        .ok_or_else(|| format!("Invalid sequence length {}", sequence_length))?;
+
+    let mut bananas: HashMap<usize, usize> = HashMap::new();
+    for secret in parse_input(&buyers)? {
+        let mut seen = HashSet::new();
+        let mut sequence = 0;
+        let mut secret = secret;
+        let mut price = secret % 10;
+        for i in 0..iterations {
+            secret = next_secret(secret);
+            let next_price = secret % 10;
+            let change = (next_price + 9 - price) as usize;
+            // drop the oldest change before shifting, so the packing never
+            // overflows even when `modulus` is close to the largest usize
+            sequence = sequence % (modulus / NUM_CHANGES) * NUM_CHANGES + change;
+            price = next_price;
+
+            // the monkey sells the first time it sees the sequence
+            if i + 1 >= sequence_length && seen.insert(sequence) {
+                *bananas.entry(sequence).or_insert(0) += price as usize;
+            }
+        }
+    }
+
+    Ok(bananas.into_values().max().unwrap_or(0))
+}
+
+pub fn day22(input: Day22Input) -> Result<Day22, String> {
+    Ok(Day22 {
+        part1: part1(input.clone())?,
+        part2: part2(input.clone())?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gets_part1() {
+        let input = Day22Input {
+            buyers: r"1
+10
+100
+2024"
+                .to_owned(),
+            iterations: 2000,
+            sequence_length: 4,
+        };
+        let result = part1(input).unwrap();
+        assert_eq!(result, 37327623);
+    }
+
+    #[test]
+    fn gets_part2() {
+        let input = Day22Input {
+            buyers: r"1
+2
+3
+2024"
+                .to_owned(),
+            iterations: 2000,
+            sequence_length: 4,
+        };
+        let result = part2(input).unwrap();
+        assert_eq!(result, 23);
+    }
+
+    #[test]
+    fn packs_the_longest_sequences() {
+        let input = |sequence_length| Day22Input {
+            buyers: "1\n2\n3\n2024".to_owned(),
+            iterations: 2000,
+            sequence_length,
+        };
+        // 19^15 is the largest power of 19 which fits
+        assert_eq!(part2(input(15)), Ok(9));
+        assert_eq!(
+            part2(input(16)),
+            Err("Invalid sequence length 16".to_owned())
+        );
+    }
+
+    #[test]
+    fn evolves_secrets() {
+        let secrets = (0..3)
+            .scan(123, |secret, _| {
+                *secret = next_secret(*secret);
+                Some(*secret)
+            })
+            .collect::<Vec<u64>>();
+        assert_eq!(secrets, vec![15887950, 16495136, 527345]);
+    }
+}
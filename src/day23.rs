@@ -0,0 +1,202 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::parse;
+
+pub struct Day23 {
+    pub part1: usize,
+    pub part2: String,
+}
+
+type Graph<'a> = HashMap<&'a str, HashSet<&'a str>>;
+
+/// The computers each computer is linked to, from lines like `kh-tc`
+pub(crate) fn parse_input(input: &str) -> Result<Graph<'_>, String> {
+    let links = parse::lines(input, |line| {
+        let (a, b) = parse::split(line, "-")?;
+        for name in [a, b] {
+            if name.is_empty() || !name.chars().all(|ch| ch.is_ascii_alphanumeric()) {
+                return Err(format!("Invalid computer name {:?}", name));
+            }
+        }
+        if a == b {
+            return Err(format!("{} is linked to itself", a));
+        }
+        Ok((a, b))
+    })?;
+
+    let mut graph: Graph = HashMap::new();
+    for (a, b) in links {
+        graph.entry(a).or_default().insert(b);
+        graph.entry(b).or_default().insert(a);
+    }
+    Ok(graph)
+}
+
+pub(crate) fn part1(input: String) -> Result<usize, String> {
+    let graph = parse_input(&input)?;
+
+    // each triangle is counted once, from its alphabetically first computer
+    let mut count = 0;
+    for (a, links) in graph.iter() {
+        for b in links.iter().filter(|b| *b > a) {
+            for c in links.intersection(&graph[b]).filter(|c| *c > b) {
+                if [a, b, c].iter().any(|name| name.starts_with('t')) {
+                    count += 1;
+                }
+            }
+        }
+    }
+
+    Ok(count)
+}
+
+/// Bron–Kerbosch with pivoting: extends the clique `r` with computers from
+/// `p`, skipping those in `x` which have already been tried.
+fn largest_clique<'a>(
+    graph: &Graph<'a>,
+    r: &mut Vec<&'a str>,
+    mut p: HashSet<&'a str>,
+    mut x: HashSet<&'a str>,
+    best: &mut Vec<&'a str>,
+) {
+    if p.is_empty() && x.is_empty() {
+        if r.len() > best.len() {
+            *best = r.clone();
+        }
+        return;
+    }
+
+    // any maximal clique includes the pivot or one of its non-neighbours
+    let pivot = p
+        .union(&x)
+        .max_by_key(|u| graph[*u].intersection(&p).count())
+        .copied();
+    let candidates = p
+        .iter()
+        .filter(|v| pivot.is_none_or(|u| !graph[u].contains(*v)))
+        .copied()
+        .collect::<Vec<&str>>();
+
+    for v in candidates {
+        let links = &graph[v];
+        r.push(v);
+        largest_clique(
+            graph,
+            r,
+            p.intersection(links).copied().collect(),
+            x.intersection(links).copied().collect(),
+            best,
+        );
+        r.pop();
+        p.remove(v);
+        x.insert(v);
+    }
+}
+
+pub(crate) fn part2(input: String) -> Result<String, String> {
+    let graph = parse_input(&input)?;
+
+    let mut best = vec![];
+    largest_clique(
+        &graph,
+        &mut vec![],
+        graph.keys().copied().collect(),
+        HashSet::new(),
+        &mut best,
+    );
+    best.sort();
+
+    Ok(best.join(","))
+}
+
+pub fn day23(input: String) -> Result<Day23, String> {
+    Ok(Day23 {
+        part1: part1(input.clone())?,
+        part2: part2(input.clone())?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gets_part1() {
+        let input = r"kh-tc
+qp-kh
+de-cg
+ka-co
+yn-aq
+qp-ub
+cg-tb
+vc-aq
+tb-ka
+wh-tc
+yn-cg
+kh-ub
+ta-co
+de-co
+tc-td
+tb-wq
+wh-td
+ta-ka
+td-qp
+aq-cg
+wq-ub
+ub-vc
+de-ta
+wq-aq
+wq-vc
+wh-yn
+ka-de
+kh-ta
+co-tc
+wh-qp
+tb-vc
+td-yn";
+
+        let result = day23(input.to_owned()).unwrap();
+
+        assert_eq!(result.part1, 7);
+    }
+
+    #[test]
+    fn gets_part2() {
+        let input = r"kh-tc
+qp-kh
+de-cg
+ka-co
+yn-aq
+qp-ub
+cg-tb
+vc-aq
+tb-ka
+wh-tc
+yn-cg
+kh-ub
+ta-co
+de-co
+tc-td
+tb-wq
+wh-td
+ta-ka
+td-qp
+aq-cg
+wq-ub
+ub-vc
+de-ta
+wq-aq
+wq-vc
+wh-yn
+ka-de
+kh-ta
+co-tc
+wh-qp
+tb-vc
+td-yn";
+
+        let result = day23(input.to_owned()).unwrap();
+
+        assert_eq!(result.part2, "co,de,ka,ta");
+    }
+}
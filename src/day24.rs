@@ -0,0 +1,326 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::parse;
+
+pub struct Day24 {
+    pub part1: u64,
+    pub part2: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Op {
+    And,
+    Or,
+    Xor,
+}
+
+impl Op {
+    pub fn apply(&self, a: bool, b: bool) -> bool {
+        match self {
+            Op::And => a && b,
+            Op::Or => a || b,
+            Op::Xor => a ^ b,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gate<'a> {
+    pub inputs: [&'a str; 2],
+    pub op: Op,
+    pub output: &'a str,
+}
+
+/// Gates wired together, with the starting values of some wires
+#[derive(Clone, Debug)]
+pub struct Circuit<'a> {
+    wires: HashMap<&'a str, bool>,
+    gates: Vec<Gate<'a>>,
+}
+
+/// The pairs of gate outputs swapped in the puzzle's adder
+const SWAPPED_PAIRS: usize = 4;
+
+fn parse_wire(line: &str) -> Result<(&str, bool), String> {
+    let (name, value) = parse::split(line, ": ")?;
+    match value {
+        "0" => Ok((name, false)),
+        "1" => Ok((name, true)),
+        _ => Err(format!("Invalid wire value {:?}", value)),
+    }
+}
+
+fn parse_gate(line: &str) -> Result<Gate<'_>, String> {
+    let (expression, output) = parse::split(line, " -> ")?;
+    let [a, op, b] = expression.split(' ').collect::<Vec<&str>>()[..] else {
+        return Err(format!(
+            "Expected a gate like \"x AND y\", got {:?}",
+            expression
+        ));
+    };
+    let op = match op {
+        "AND" => Op::And,
+        "OR" => Op::Or,
+        "XOR" => Op::Xor,
+        _ => return Err(format!("Unknown gate {:?}", op)),
+    };
+    Ok(Gate {
+        inputs: [a, b],
+        op,
+        output,
+    })
+}
+
+/// The wire values, then a blank line, then one gate per line
+pub(crate) fn parse_input(input: &str) -> Result<Circuit<'_>, String> {
+    let mut wires = HashMap::new();
+    let mut gates = vec![];
+    let mut in_gates = false;
+    for (i, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            in_gates = true;
+        } else if in_gates {
+            gates.push(parse::on_line(i, parse_gate(line))?);
+        } else {
+            let (name, value) = parse::on_line(i, parse_wire(line))?;
+            if wires.insert(name, value).is_some() {
+                return parse::on_line(i, Err(format!("Wire {} has two values", name)));
+            }
+        }
+    }
+    Circuit::new(wires, gates)
+}
+
+impl<'a> Circuit<'a> {
+    /// A circuit where each wire is either given a starting value or is the
+    /// output of exactly one gate
+    pub fn new(wires: HashMap<&'a str, bool>, gates: Vec<Gate<'a>>) -> Result<Self, String> {
+        let mut outputs = HashSet::new();
+        for gate in &gates {
+            if !outputs.insert(gate.output) || wires.contains_key(gate.output) {
+                return Err(format!("Wire {} has two values", gate.output));
+            }
+        }
+        Ok(Circuit { wires, gates })
+    }
+
+    /// The starting values of the wires which aren't gate outputs
+    pub fn wires(&self) -> &HashMap<&'a str, bool> {
+        &self.wires
+    }
+
+    pub fn gates(&self) -> &[Gate<'a>] {
+        &self.gates
+    }
+
+    /// The value of every wire once the gates settle, starting from `wires`
+    pub fn evaluate(
+        &self,
+        wires: &HashMap<&'a str, bool>,
+    ) -> Result<HashMap<&'a str, bool>, String> {
+        let mut values = wires.clone();
+        let mut remaining = self.gates.iter().collect::<Vec<&Gate>>();
+        while !remaining.is_empty() {
+            let before = remaining.len();
+            remaining.retain(|gate| {
+                match (values.get(gate.inputs[0]), values.get(gate.inputs[1])) {
+                    (Some(a), Some(b)) => {
+                        values.insert(gate.output, gate.op.apply(*a, *b));
+                        false
+                    }
+                    _ => true,
+                }
+            });
+            if remaining.len() == before {
+                return Err(format!("Wire {} never gets a value", remaining[0].output));
+            }
+        }
+        Ok(values)
+    }
+
+    /// The gate outputs which break the ripple-carry adder pattern: each
+    /// output bit is an XOR of the input bits' XOR and the carry, apart from
+    /// the last, which is the final carry and so comes from an OR.
+    fn swapped_wires(&self) -> Vec<&'a str> {
+        let last_z = self
+            .gates
+            .iter()
+            .map(|gate| gate.output)
+            .filter(|output| output.starts_with('z'))
+            .max()
+            .unwrap_or("");
+        let is_input = |wire: &str| wire.starts_with('x') || wire.starts_with('y');
+        let feeds = |output: &str, op: Op| {
+            self.gates
+                .iter()
+                .any(|gate| gate.op == op && gate.inputs.contains(&output))
+        };
+        let feeds_other_than = |output: &str, op: Op| {
+            self.gates
+                .iter()
+                .any(|gate| gate.op != op && gate.inputs.contains(&output))
+        };
+
+        let mut swapped = self
+            .gates
+            .iter()
+            .filter(|gate| {
+                let output = gate.output;
+                let from_inputs = gate.inputs.iter().all(|wire| is_input(wire));
+                let first_bit = gate.inputs.contains(&"x00");
+                match gate.op {
+                    _ if output == last_z => gate.op != Op::Or,
+                    _ if output.starts_with('z') => gate.op != Op::Xor,
+                    Op::Xor if !from_inputs => !output.starts_with('z'),
+                    // the XOR of two input bits is their sum without the carry,
+                    // so it belongs in the output XOR and the carry AND; only
+                    // the carry ANDs feed the ORs
+                    Op::Xor => feeds(output, Op::Or),
+                    Op::And if !first_bit => feeds_other_than(output, Op::Or),
+                    Op::And | Op::Or => false,
+                }
+            })
+            .map(|gate| gate.output)
+            .collect::<Vec<&str>>();
+        swapped.sort();
+        swapped
+    }
+}
+
+/// The number whose bits are the wires starting with `prefix`, e.g. z00 is
+/// the lowest bit of z
+fn number(values: &HashMap<&str, bool>, prefix: char) -> Result<u64, String> {
+    values
+        .iter()
+        .filter(|(wire, _)| wire.starts_with(prefix))
+        .try_fold(0, |n, (wire, value)| {
+            let bit = parse::number::<u32>(&wire[1..])?;
+            let mask = 1_u64
+                .checked_shl(bit)
+                .ok_or_else(|| format!("Wire {} is beyond bit 63", wire))?;
+            Ok(if *value { n | mask } else { n })
+        })
+}
+
+pub(crate) fn part1(input: String) -> Result<u64, String> {
+    let circuit = parse_input(&input)?;
+    number(&circuit.evaluate(&circuit.wires)?, 'z')
+}
+
+pub(crate) fn part2(input: String) -> Result<String, String> {
+    let circuit = parse_input(&input)?;
+    let swapped = circuit.swapped_wires();
+    if swapped.len() != SWAPPED_PAIRS * 2 {
+        return Err(format!(
+            "Expected {} swapped wires, found {}: {}",
+            SWAPPED_PAIRS * 2,
+            swapped.len(),
+            swapped.join(",")
+        ));
+    }
+    Ok(swapped.join(","))
+}
+
+pub fn day24(input: String) -> Result<Day24, String> {
+    Ok(Day24 {
+        part1: part1(input.clone())?,
+        part2: part2(input.clone())?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gets_part1() {
+        let input = r"x00: 1
+x01: 1
+x02: 1
+y00: 0
+y01: 1
+y02: 0
+
+x00 AND y00 -> z00
+x01 XOR y01 -> z01
+x02 OR y02 -> z02";
+
+        let result = part1(input.to_owned()).unwrap();
+
+        assert_eq!(result, 4);
+    }
+
+    #[test]
+    fn finds_swapped_adder_wires() {
+        // a 3-bit adder with the outputs of z02 and a02 swapped
+        let input = r"x00: 1
+x01: 0
+x02: 1
+y00: 1
+y01: 1
+y02: 0
+
+x00 XOR y00 -> z00
+x00 AND y00 -> c00
+x01 XOR y01 -> s01
+s01 XOR c00 -> z01
+x01 AND y01 -> a01
+s01 AND c00 -> b01
+a01 OR b01 -> c01
+x02 XOR y02 -> s02
+s02 XOR c01 -> a02
+x02 AND y02 -> z02
+s02 AND c01 -> b02
+a02 OR b02 -> z03";
+
+        let circuit = parse_input(input).unwrap();
+        assert_eq!(circuit.swapped_wires(), vec!["a02", "z02"]);
+
+        let fixed = input
+            .replace("-> a02", "-> tmp")
+            .replace("-> z02", "-> a02")
+            .replace("-> tmp", "-> z02");
+        let circuit = parse_input(&fixed).unwrap();
+        assert!(circuit.swapped_wires().is_empty());
+
+        let values = circuit.evaluate(&circuit.wires).unwrap();
+        assert_eq!(number(&values, 'z'), Ok(5 + 3));
+
+        let final_carry_swapped = fixed
+            .replace("-> z02", "-> tmp")
+            .replace("-> z03", "-> z02")
+            .replace("-> tmp", "-> z03");
+        let circuit = parse_input(&final_carry_swapped).unwrap();
+        assert_eq!(circuit.swapped_wires(), vec!["z02", "z03"]);
+    }
+
+    #[test]
+    fn evaluates_built_circuits() {
+        let gates = vec![
+            Gate {
+                inputs: ["a", "b"],
+                op: Op::Xor,
+                output: "sum",
+            },
+            Gate {
+                inputs: ["a", "b"],
+                op: Op::And,
+                output: "carry",
+            },
+        ];
+        let circuit = Circuit::new(HashMap::new(), gates.clone()).unwrap();
+
+        let values = circuit
+            .evaluate(&HashMap::from([("a", true), ("b", true)]))
+            .unwrap();
+        assert_eq!((values["sum"], values["carry"]), (false, true));
+        assert_eq!(
+            circuit.evaluate(&HashMap::from([("a", true)])),
+            Err("Wire sum never gets a value".to_owned())
+        );
+        assert_eq!(
+            Circuit::new(HashMap::from([("sum", false)]), gates).err(),
+            Some("Wire sum has two values".to_owned())
+        );
+    }
+}
@@ -0,0 +1,181 @@
+use crate::parse;
+
+pub struct Day25 {
+    pub part1: usize,
+}
+
+/// Column heights of the locks and keys, and the room inside each schematic
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Schematics {
+    locks: Vec<Vec<usize>>,
+    keys: Vec<Vec<usize>>,
+    space: usize,
+}
+
+/// The height of each column, which must be a solid run of # from `rows[0]`
+fn heights<'a>(rows: impl Iterator<Item = &'a Vec<char>> + Clone) -> Result<Vec<usize>, String> {
+    let width = rows.clone().next().map_or(0, |row| row.len());
+    (0..width)
+        .map(|x| {
+            let column = rows.clone().map(|row| row[x]).collect::<String>();
+            let height = column.chars().take_while(|ch| *ch == '#').count();
+            if column[height..].contains('#') {
+                return Err(format!("Column {} has a gap", x + 1));
+            }
+            Ok(height - 1)
+        })
+        .collect()
+}
+
+fn parse_schematic(text: &str) -> Result<(bool, Vec<usize>), String> {
+    let grid = parse::grid(text)?;
+    parse::only_chars(&grid, "#.")?;
+    let full = |row: &Vec<char>| row.iter().all(|ch| *ch == '#');
+    match (full(&grid[0]), full(&grid[grid.len() - 1])) {
+        // locks hang from the top, keys stand on the bottom
+        (true, false) => Ok((true, heights(grid.iter())?)),
+        (false, true) => Ok((false, heights(grid.iter().rev())?)),
+        _ => Err("Expected a full row of # at either the top or the bottom".to_owned()),
+    }
+}
+
+/// Schematics are blocks of # and . separated by blank lines
+pub(crate) fn parse_input(input: &str) -> Result<Schematics, String> {
+    let mut schematics = Schematics::default();
+    let mut size = None;
+    let blocks = input.split("\n\n").map(str::trim).filter(|b| !b.is_empty());
+    for (i, block) in blocks.enumerate() {
+        let (is_lock, heights) =
+            parse_schematic(block).map_err(|e| format!("Schematic {}: {}", i + 1, e))?;
+        let rows = block.lines().count();
+        match size {
+            None => size = Some((rows, heights.len())),
+            Some(size) if size != (rows, heights.len()) => {
+                return Err(format!(
+                    "Schematic {}: Expected {} rows of {}, got {} of {}",
+                    i + 1,
+                    size.0,
+                    size.1,
+                    rows,
+                    heights.len()
+                ));
+            }
+            Some(_) => {}
+        }
+        if is_lock {
+            schematics.locks.push(heights);
+        } else {
+            schematics.keys.push(heights);
+        }
+    }
+    schematics.space = size.ok_or("No schematics")?.0 - 2;
+    Ok(schematics)
+}
+
+pub(crate) fn part1(input: String) -> Result<usize, String> {
+    let schematics = parse_input(&input)?;
+    let fits = |lock: &Vec<usize>, key: &Vec<usize>| {
+        lock.iter().zip(key).all(|(l, k)| l + k <= schematics.space)
+    };
+    Ok(schematics
+        .locks
+        .iter()
+        .map(|lock| schematics.keys.iter().filter(|key| fits(lock, key)).count())
+        .sum())
+}
+
+pub fn day25(input: String) -> Result<Day25, String> {
+    Ok(Day25 {
+        part1: part1(input)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gets_part1() {
+        let input = r"#####
+.####
+.####
+.####
+.#.#.
+.#...
+.....
+
+#####
+##.##
+.#.##
+...##
+...#.
+...#.
+.....
+
+.....
+#....
+#....
+#...#
+#.#.#
+#.###
+#####
+
+.....
+.....
+#.#..
+###..
+###.#
+###.#
+#####
+
+.....
+.....
+.....
+#....
+#.#..
+#.#.#
+#####";
+
+        let result = day25(input.to_owned()).unwrap();
+
+        assert_eq!(result.part1, 3);
+    }
+
+    #[test]
+    fn reads_heights() {
+        let input = r"#####
+.####
+.####
+.####
+.#.#.
+.#...
+.....
+
+.....
+#....
+#....
+#...#
+#.#.#
+#.###
+#####";
+
+        let schematics = parse_input(input).unwrap();
+
+        assert_eq!(schematics.locks, vec![vec![0, 5, 3, 4, 3]]);
+        assert_eq!(schematics.keys, vec![vec![5, 0, 2, 1, 3]]);
+        assert_eq!(schematics.space, 5);
+    }
+
+    #[test]
+    fn rejects_malformed_schematics() {
+        assert_eq!(
+            parse_input("#####\n.#...\n#####\n....."),
+            Err("Schematic 1: Column 1 has a gap".to_owned())
+        );
+        assert_eq!(
+            parse_input("#####\n.....\n\n..#..\n....."),
+            Err("Schematic 2: Expected a full row of # at either the top or the bottom".to_owned())
+        );
+        assert!(parse_input("#####\n.....\n\n#####\n.....\n.....").is_err());
+    }
+}
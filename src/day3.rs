@@ -1,80 +1,351 @@
-use std::char;
+use std::fmt;
+use std::io::{ErrorKind, Read};
+use std::ops::Range;
+
+use crate::explain::Explanation;
 
 pub struct Day3 {
-    pub part1: i32,
-    pub part2: i32,
+    pub part1: i64,
+    pub part2: i64,
 }
 
-fn scan_program(input: String, with_do_dont: bool) -> i32 {
-    let mut sum = 0;
-    let mut last_char: Option<char> = None;
-    let mut is_enabled = true;
-    let mut left: Option<String> = None;
-    let mut right: Option<String> = None;
+#[derive(Clone, Debug, PartialEq)]
+pub enum TokenKind<'a> {
+    Word(&'a str),
+    Number(&'a str),
+    Open,
+    Close,
+    Comma,
+    /// Any other character, which is corruption between instructions
+    Other,
+}
 
-    let mut skip = 0;
+/// A token and the byte range of the memory it came from
+#[derive(Clone, Debug, PartialEq)]
+pub struct Token<'a> {
+    pub kind: TokenKind<'a>,
+    pub span: Range<usize>,
+}
 
-    for (i, c) in input.chars().enumerate() {
-        if skip > 0 {
-            skip -= 1;
-            continue;
-        }
+fn is_word_char(ch: char) -> bool {
+    ch.is_ascii_alphabetic() || ch == '\''
+}
 
-        if c == 'd' && with_do_dont {
-            if input[i..i + 4].to_owned() == "do()" {
-                is_enabled = true;
-                skip = 3;
-            } else if input[i..i + 7].to_owned() == "don't()" {
-                is_enabled = false;
-                skip = 6;
+/// Splits memory into runs of letters, runs of digits and single punctuation
+/// characters
+pub fn tokenize(input: &str) -> Vec<Token<'_>> {
+    let mut tokens = vec![];
+    let mut chars = input.char_indices().peekable();
+    while let Some((start, ch)) = chars.next() {
+        let mut end = start + ch.len_utf8();
+        let mut take_while = |matches: fn(char) -> bool| {
+            while let Some((i, next)) = chars.next_if(|(_, next)| matches(*next)) {
+                end = i + next.len_utf8();
             }
+            end
+        };
+        let kind = match ch {
+            _ if is_word_char(ch) => TokenKind::Word(&input[start..take_while(is_word_char)]),
+            _ if ch.is_ascii_digit() => {
+                TokenKind::Number(&input[start..take_while(|ch| ch.is_ascii_digit())])
+            }
+            '(' => TokenKind::Open,
+            ')' => TokenKind::Close,
+            ',' => TokenKind::Comma,
+            _ => TokenKind::Other,
+        };
+        tokens.push(Token {
+            kind,
+            span: start..end,
+        });
+    }
+    tokens
+}
+
+/// What the instructions act on
+#[derive(Clone, Debug, PartialEq)]
+pub struct Machine {
+    pub enabled: bool,
+    pub sum: i64,
+}
+
+impl Default for Machine {
+    fn default() -> Self {
+        Machine {
+            enabled: true,
+            sum: 0,
         }
-        if !is_enabled {
-            continue;
+    }
+}
+
+/// An instruction the interpreter recognises. Calls with the wrong number of
+/// arguments, or arguments longer than `max_digits`, are corruption.
+#[derive(Clone, Copy, Debug)]
+pub struct Definition {
+    pub name: &'static str,
+    pub arity: usize,
+    pub max_digits: usize,
+    pub run: fn(&mut Machine, &[i64]) -> Result<(), String>,
+}
+
+pub const MUL: Definition = Definition {
+    name: "mul",
+    arity: 2,
+    max_digits: 3,
+    run: |machine, args| {
+        if machine.enabled {
+            machine.sum = args[0]
+                .checked_mul(args[1])
+                .and_then(|product| machine.sum.checked_add(product))
+                .ok_or_else(|| "Sum of products is too large".to_owned())?;
         }
-        if c == 'm' && input[i..i + 4].to_owned() == "mul(" {
-            last_char = Some('(');
-            left = None;
-            right = None;
-            skip = 3;
-        } else if let Some(l) = last_char {
-            if l == '(' {
-                if c == ',' && left.is_some() {
-                    last_char = Some(',');
-                } else if c.is_digit(10) {
-                    left = Some(format!("{}{}", left.unwrap_or("".to_owned()), c).to_owned());
-                } else {
-                    last_char = None;
+        Ok(())
+    },
+};
+
+pub const DO: Definition = Definition {
+    name: "do",
+    arity: 0,
+    max_digits: 0,
+    run: |machine, _| {
+        machine.enabled = true;
+        Ok(())
+    },
+};
+
+pub const DONT: Definition = Definition {
+    name: "don't",
+    arity: 0,
+    max_digits: 0,
+    run: |machine, _| {
+        machine.enabled = false;
+        Ok(())
+    },
+};
+
+/// A recognised instruction and the byte offset of its name
+#[derive(Clone, Debug, PartialEq)]
+pub struct Instruction {
+    pub name: &'static str,
+    pub args: Vec<i64>,
+    pub offset: usize,
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let args = self
+            .args
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect::<Vec<String>>();
+        write!(f, "{}({})", self.name, args.join(","))
+    }
+}
+
+/// The arguments of a call starting at the token after its `(`, and the
+/// index of the token after its `)`
+fn arguments(tokens: &[Token], start: usize, definition: &Definition) -> Option<(Vec<i64>, usize)> {
+    let mut args = vec![];
+    let mut i = start;
+    loop {
+        match (&tokens.get(i)?.kind, args.is_empty()) {
+            (TokenKind::Close, true) => break,
+            (TokenKind::Number(digits), _) if digits.len() <= definition.max_digits => {
+                args.push(digits.parse().ok()?);
+                match tokens.get(i + 1)?.kind {
+                    TokenKind::Comma => i += 2,
+                    TokenKind::Close => {
+                        i += 1;
+                        break;
+                    }
+                    _ => return None,
                 }
-            } else if l == ',' {
-                if c == ')' {
-                    match (left.clone(), right.clone()) {
-                        (Some(lv), Some(rv)) => {
-                            let value_left = str::parse::<i32>(&lv).unwrap();
-                            let value_right = str::parse::<i32>(&rv).unwrap();
-
-                            sum += value_left * value_right;
-                        }
-                        _ => {}
+            }
+            _ => return None,
+        }
+    }
+    (args.len() == definition.arity).then_some((args, i + 1))
+}
+
+/// Every well-formed call of an instruction in `set`, with the index of the
+/// token after it
+fn calls(tokens: &[Token], set: &[Definition]) -> Vec<(Instruction, usize)> {
+    let mut calls = vec![];
+    let mut i = 0;
+    while i < tokens.len() {
+        let call = match (&tokens[i].kind, tokens.get(i + 1).map(|t| &t.kind)) {
+            (TokenKind::Word(word), Some(TokenKind::Open)) => set
+                .iter()
+                .filter(|definition| word.ends_with(definition.name))
+                .max_by_key(|definition| definition.name.len())
+                .and_then(|definition| {
+                    let (args, next) = arguments(tokens, i + 2, definition)?;
+                    let instruction = Instruction {
+                        name: definition.name,
+                        args,
+                        offset: tokens[i].span.end - definition.name.len(),
                     };
-                    last_char = None;
-                } else if c.is_digit(10) {
-                    right = Some(format!("{}{}", right.unwrap_or("".to_owned()), c).to_owned());
-                } else {
-                    last_char = None;
-                }
+                    Some((instruction, next))
+                }),
+            _ => None,
+        };
+        match call {
+            Some((instruction, next)) => {
+                calls.push((instruction, next));
+                i = next;
             }
+            None => i += 1,
         }
     }
+    calls
+}
 
-    sum
+/// Every well-formed call of an instruction in `set`. Names may have
+/// corruption in front of them, e.g. `xmul(2,4)` is `mul(2,4)`.
+pub fn parse(tokens: &[Token], set: &[Definition]) -> Vec<Instruction> {
+    calls(tokens, set)
+        .into_iter()
+        .map(|(instruction, _)| instruction)
+        .collect()
 }
 
-pub fn day3(input: String) -> Day3 {
-    let part1 = scan_program(input.to_owned(), false);
-    let part2 = scan_program(input.to_owned(), true);
+/// Runs one instruction on the machine
+pub fn step(
+    machine: &mut Machine,
+    instruction: &Instruction,
+    set: &[Definition],
+) -> Result<(), String> {
+    let definition = set
+        .iter()
+        .find(|definition| definition.name == instruction.name)
+        .ok_or_else(|| format!("Unknown instruction {}", instruction.name))?;
+    (definition.run)(machine, &instruction.args)
+}
 
-    Day3 { part1, part2 }
+/// Runs the instructions in order on a fresh machine
+pub fn interpret(instructions: &[Instruction], set: &[Definition]) -> Result<Machine, String> {
+    let mut machine = Machine::default();
+    for instruction in instructions {
+        step(&mut machine, instruction, set)?;
+    }
+    Ok(machine)
+}
+
+/// The most bytes a call of an instruction in `set` can take up
+fn longest_call(set: &[Definition]) -> usize {
+    set.iter()
+        .map(|d| d.name.len() + 2 + d.arity * d.max_digits + d.arity.saturating_sub(1))
+        .max()
+        .unwrap_or(0)
+}
+
+/// Finds the instructions in memory read `chunk_size` bytes at a time,
+/// calling `found` with each in order. Only the unscanned end of memory which
+/// could still hold the start of a call is kept between chunks, so memory use
+/// doesn't grow with the size of the dump.
+pub fn scan(
+    mut reader: impl Read,
+    set: &[Definition],
+    chunk_size: usize,
+    mut found: impl FnMut(Instruction) -> Result<(), String>,
+) -> Result<(), String> {
+    if chunk_size == 0 {
+        return Err("Chunk size must be at least 1".to_owned());
+    }
+    let longest = longest_call(set);
+    let mut chunk = vec![0; chunk_size];
+    let mut window: Vec<u8> = Vec::with_capacity(longest + chunk_size);
+    // the offset in memory of the start of the window, and of the end of the
+    // last call found, which may reach past the part of the window scanned
+    let mut window_offset = 0;
+    let mut scanned_to = 0;
+
+    loop {
+        let read = match reader.read(&mut chunk) {
+            Ok(read) => read,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(format!("Error reading memory: {}", e)),
+        };
+        // other bytes can't be part of a call, so swapping them for NUL keeps
+        // the window ASCII without moving any offsets
+        window.extend(
+            chunk[..read]
+                .iter()
+                .map(|b| if b.is_ascii() { *b } else { 0 }),
+        );
+
+        // a call starting before the cut is complete within the window
+        let cut = if read == 0 {
+            window.len()
+        } else {
+            window.len().saturating_sub(longest)
+        };
+        let text = std::str::from_utf8(&window).map_err(|e| e.to_string())?;
+        let tokens = tokenize(text);
+        for (mut instruction, next) in calls(&tokens, set) {
+            instruction.offset += window_offset;
+            if instruction.offset < scanned_to {
+                continue;
+            }
+            if instruction.offset >= window_offset + cut {
+                break;
+            }
+            scanned_to = window_offset + tokens[next - 1].span.end;
+            found(instruction)?;
+        }
+
+        if read == 0 {
+            return Ok(());
+        }
+        window.drain(..cut);
+        window_offset += cut;
+    }
+}
+
+/// Solves both parts from a reader, for memory dumps too large to load
+pub fn day3_stream(reader: impl Read, chunk_size: usize) -> Result<Day3, String> {
+    let mut part1 = Machine::default();
+    let mut part2 = Machine::default();
+    scan(reader, &[MUL, DO, DONT], chunk_size, |instruction| {
+        if instruction.name == MUL.name {
+            step(&mut part1, &instruction, &[MUL])?;
+        }
+        step(&mut part2, &instruction, &[MUL, DO, DONT])
+    })?;
+    Ok(Day3 {
+        part1: part1.sum,
+        part2: part2.sum,
+    })
+}
+
+fn run_program(input: &str, set: &[Definition]) -> Result<i64, String> {
+    let instructions = parse(&tokenize(input), set);
+    Ok(interpret(&instructions, set)?.sum)
+}
+
+pub fn day3(input: String) -> Result<Day3, String> {
+    Ok(Day3 {
+        part1: run_program(&input, &[MUL])?,
+        part2: run_program(&input, &[MUL, DO, DONT])?,
+    })
+}
+
+/// Lists every instruction each part recognises, with its offset
+pub fn explain(input: String) -> Result<Vec<Explanation>, String> {
+    let tokens = tokenize(&input);
+    let sets: [(u8, &[Definition]); 2] = [(1, &[MUL]), (2, &[MUL, DO, DONT])];
+    Ok(sets
+        .iter()
+        .flat_map(|(part, set)| {
+            parse(&tokens, set).into_iter().map(|instruction| {
+                Explanation::new(
+                    *part,
+                    format!("offset {}", instruction.offset),
+                    instruction.to_string(),
+                )
+            })
+        })
+        .collect())
 }
 
 #[cfg(test)]
@@ -85,7 +356,7 @@ mod tests {
     fn gets_part1() {
         let input = r"xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))";
 
-        let result = day3(input.to_owned());
+        let result = day3(input.to_owned()).unwrap();
 
         assert_eq!(result.part1, 161);
     }
@@ -94,8 +365,83 @@ mod tests {
     fn gets_part2() {
         let input = r"xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
 
-        let result = day3(input.to_owned());
+        let result = day3(input.to_owned()).unwrap();
 
         assert_eq!(result.part2, 48);
     }
+
+    #[test]
+    fn lists_instructions() {
+        let input = r"é mul(1,2)don't()mul(1234,5)mul(4)mul(3,4";
+
+        let instructions = parse(&tokenize(input), &[MUL, DO, DONT]);
+
+        assert_eq!(
+            instructions,
+            vec![
+                Instruction {
+                    name: "mul",
+                    args: vec![1, 2],
+                    offset: 3,
+                },
+                Instruction {
+                    name: "don't",
+                    args: vec![],
+                    offset: 11,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn runs_new_instructions() {
+        const ADD: Definition = Definition {
+            name: "add",
+            arity: 3,
+            max_digits: 4,
+            run: |machine, args| {
+                machine.sum += args.iter().sum::<i64>();
+                Ok(())
+            },
+        };
+        const SUB: Definition = Definition {
+            name: "sub",
+            arity: 1,
+            max_digits: 4,
+            run: |machine, args| {
+                machine.sum -= args[0];
+                Ok(())
+            },
+        };
+        let input = r"add(1000,200,30)xsub(4)add(1,2)mul(2,3)sub(10000)";
+
+        let result = run_program(input, &[ADD, SUB, MUL]).unwrap();
+
+        assert_eq!(result, 1230 - 4 + 6);
+    }
+
+    #[test]
+    fn scans_across_chunks() {
+        let input =
+            "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)ümul(1234,5)undo()?mul(8,5))";
+        let whole = parse(&tokenize(input), &[MUL, DO, DONT]);
+
+        for chunk_size in 1..=input.len() {
+            let mut found = vec![];
+            scan(
+                input.as_bytes(),
+                &[MUL, DO, DONT],
+                chunk_size,
+                |instruction| {
+                    found.push(instruction);
+                    Ok(())
+                },
+            )
+            .unwrap();
+            assert_eq!(found, whole, "chunks of {}", chunk_size);
+
+            let result = day3_stream(input.as_bytes(), chunk_size).unwrap();
+            assert_eq!((result.part1, result.part2), (161, 48));
+        }
+    }
 }
@@ -1,213 +1,203 @@
-use std::char;
+use std::collections::HashMap;
+
+use crate::parse;
 
 pub struct Day4 {
     pub part1: usize,
     pub part2: usize,
 }
 
-const WORD: &str = "XMAS";
-const WORD_LEN: usize = WORD.len();
-
-fn word_search(input: String) -> usize {
-    let line_len = input.lines().next().unwrap().len();
-    let num_lines = input.lines().count();
-    let word_vec = WORD.chars().collect::<Vec<char>>();
-
-    input
-        .lines()
-        .enumerate()
-        .fold(vec![], |acc, (i, line)| {
-            let mut next = acc.clone();
-
-            for (j, _) in line.chars().enumerate().filter(|(j, ch)| {
-                *ch == word_vec[0] && !acc.iter().any(|(y, x, _)| *y == i && x == j)
-            }) {
-                let match_0 = j <= line_len - WORD_LEN
-                    && WORD
-                        .chars()
-                        .enumerate()
-                        .skip(1)
-                        .all(|(w, wch)| line.chars().nth(j + w).unwrap() == wch);
-
-                let match_45 = j <= line_len - WORD_LEN
-                    && i >= WORD_LEN - 1
-                    && WORD.chars().enumerate().skip(1).all(|(w, wch)| {
-                        input
-                            .lines()
-                            .nth(i - w)
-                            .unwrap()
-                            .chars()
-                            .nth(j + w)
-                            .unwrap()
-                            == wch
-                    });
+/// The ways a word can run through the grid
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    East,
+    NorthEast,
+    North,
+    NorthWest,
+    West,
+    SouthWest,
+    South,
+    SouthEast,
+}
 
-                let match_90 = i >= WORD_LEN - 1
-                    && WORD.chars().enumerate().skip(1).all(|(w, wch)| {
-                        input.lines().nth(i - w).unwrap().chars().nth(j).unwrap() == wch
-                    });
+impl Direction {
+    pub const ALL: [Direction; 8] = [
+        Direction::East,
+        Direction::NorthEast,
+        Direction::North,
+        Direction::NorthWest,
+        Direction::West,
+        Direction::SouthWest,
+        Direction::South,
+        Direction::SouthEast,
+    ];
 
-                let match_135 = j >= WORD_LEN - 1
-                    && i >= WORD_LEN - 1
-                    && WORD.chars().enumerate().skip(1).all(|(w, wch)| {
-                        input
-                            .lines()
-                            .nth(i - w)
-                            .unwrap()
-                            .chars()
-                            .nth(j - w)
-                            .unwrap()
-                            == wch
-                    });
+    /// The (x, y) step to the next letter, with y going down the grid
+    fn step(self) -> (isize, isize) {
+        match self {
+            Direction::East => (1, 0),
+            Direction::NorthEast => (1, -1),
+            Direction::North => (0, -1),
+            Direction::NorthWest => (-1, -1),
+            Direction::West => (-1, 0),
+            Direction::SouthWest => (-1, 1),
+            Direction::South => (0, 1),
+            Direction::SouthEast => (1, 1),
+        }
+    }
+}
 
-                let match_180 = j >= WORD_LEN - 1
-                    && WORD
-                        .chars()
-                        .enumerate()
-                        .skip(1)
-                        .all(|(w, wch)| line.chars().nth(j - w).unwrap() == wch);
-
-                let match_225 = j >= WORD_LEN - 1
-                    && i <= num_lines - WORD_LEN
-                    && WORD.chars().enumerate().skip(1).all(|(w, wch)| {
-                        input
-                            .lines()
-                            .nth(i + w)
-                            .unwrap()
-                            .chars()
-                            .nth(j - w)
-                            .unwrap()
-                            == wch
-                    });
+/// A word found in the grid, starting from (x, y)
+#[derive(Clone, Debug, PartialEq)]
+pub struct Match<'a> {
+    pub word: &'a str,
+    pub x: usize,
+    pub y: usize,
+    pub direction: Direction,
+}
 
-                let match_270 = i <= num_lines - WORD_LEN
-                    && WORD.chars().enumerate().skip(1).all(|(w, wch)| {
-                        input.lines().nth(i + w).unwrap().chars().nth(j).unwrap() == wch
-                    });
+/// Every place any of `words` appears in the grid, reading in any of the
+/// eight directions. Each cell is visited once, trying only the words which
+/// start with its letter. Words listed twice are only searched for once, and
+/// one-letter words, which read the same every way, are found facing east.
+pub fn word_search<'a>(grid: &[Vec<char>], words: &[&'a str]) -> Vec<Match<'a>> {
+    let mut by_first: HashMap<char, Vec<(&'a str, Vec<char>)>> = HashMap::new();
+    for word in words {
+        if let Some(first) = word.chars().next() {
+            let same_start = by_first.entry(first).or_default();
+            if same_start.iter().all(|(other, _)| other != word) {
+                same_start.push((word, word.chars().collect()));
+            }
+        }
+    }
 
-                let match_315 = j <= line_len - WORD_LEN
-                    && i <= num_lines - WORD_LEN
-                    && WORD.chars().enumerate().skip(1).all(|(w, wch)| {
-                        input
-                            .lines()
-                            .nth(i + w)
-                            .unwrap()
-                            .chars()
-                            .nth(j + w)
-                            .unwrap()
-                            == wch
-                    });
+    let letter = |x: isize, y: isize| {
+        let row = grid.get(usize::try_from(y).ok()?)?;
+        row.get(usize::try_from(x).ok()?).copied()
+    };
 
-                let num_matches = (if match_0 { 1 } else { 0 })
-                    + (if match_45 { 1 } else { 0 })
-                    + (if match_90 { 1 } else { 0 })
-                    + (if match_135 { 1 } else { 0 })
-                    + (if match_180 { 1 } else { 0 })
-                    + (if match_225 { 1 } else { 0 })
-                    + (if match_270 { 1 } else { 0 })
-                    + (if match_315 { 1 } else { 0 });
-
-                if num_matches > 0 {
-                    next.push((i, j, num_matches));
+    let mut matches = vec![];
+    for (y, row) in grid.iter().enumerate() {
+        for (x, ch) in row.iter().enumerate() {
+            for (word, letters) in by_first.get(ch).into_iter().flatten() {
+                let directions = match letters.len() {
+                    1 => &Direction::ALL[..1],
+                    _ => &Direction::ALL[..],
+                };
+                for &direction in directions {
+                    let (dx, dy) = direction.step();
+                    let found = letters.iter().enumerate().skip(1).all(|(i, wch)| {
+                        let i = i as isize;
+                        letter(x as isize + dx * i, y as isize + dy * i) == Some(*wch)
+                    });
+                    if found {
+                        matches.push(Match {
+                            word,
+                            x,
+                            y,
+                            direction,
+                        });
+                    }
                 }
             }
+        }
+    }
+    matches
+}
 
-            next
-        })
-        .iter()
-        .fold(0, |acc, (_, _, count)| acc + count)
+/// A small grid of letters to look for, where `.` matches any letter
+#[derive(Clone, Debug, PartialEq)]
+pub struct Template {
+    cells: Vec<Vec<char>>,
+}
+
+/// How a template was turned, clockwise, and whether it was first mirrored
+/// left to right
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    pub rotation: u16,
+    pub mirrored: bool,
+}
+
+/// Where a template matched, by the top left of the turned template
+#[derive(Clone, Debug, PartialEq)]
+pub struct Placement {
+    pub x: usize,
+    pub y: usize,
+    pub transform: Transform,
+}
+
+const WILDCARD: char = '.';
+
+/// Two MAS crossing at the A
+pub const X_MAS: &str = "M.S\n.A.\nM.S";
+
+fn rotate(cells: &[Vec<char>]) -> Vec<Vec<char>> {
+    let height = cells.len();
+    (0..cells[0].len())
+        .map(|x| (0..height).map(|y| cells[height - 1 - y][x]).collect())
+        .collect()
 }
 
-fn xmas(input: String) -> usize {
-    let line_len = input.lines().next().unwrap().len();
-    let num_lines = input.lines().count();
-
-    input
-        .lines()
-        .enumerate()
-        .filter(|(i, _)| *i > 0 && *i < num_lines - 1)
-        .fold(0, |acc, (i, line)| {
-            acc + line
-                .chars()
-                .enumerate()
-                .filter(|(j, ch)| {
-                    *ch == 'A'
-                        && *j > 0
-                        && *j < line_len - 1
-                        && ((input
-                            .lines()
-                            .nth(i - 1)
-                            .unwrap()
-                            .chars()
-                            .nth(j - 1)
-                            .unwrap()
-                            == 'M'
-                            && input
-                                .lines()
-                                .nth(i + 1)
-                                .unwrap()
-                                .chars()
-                                .nth(j + 1)
-                                .unwrap()
-                                == 'S')
-                            || (input
-                                .lines()
-                                .nth(i - 1)
-                                .unwrap()
-                                .chars()
-                                .nth(j - 1)
-                                .unwrap()
-                                == 'S'
-                                && input
-                                    .lines()
-                                    .nth(i + 1)
-                                    .unwrap()
-                                    .chars()
-                                    .nth(j + 1)
-                                    .unwrap()
-                                    == 'M'))
-                        && ((input
-                            .lines()
-                            .nth(i - 1)
-                            .unwrap()
-                            .chars()
-                            .nth(j + 1)
-                            .unwrap()
-                            == 'M'
-                            && input
-                                .lines()
-                                .nth(i + 1)
-                                .unwrap()
-                                .chars()
-                                .nth(j - 1)
-                                .unwrap()
-                                == 'S')
-                            || (input
-                                .lines()
-                                .nth(i - 1)
-                                .unwrap()
-                                .chars()
-                                .nth(j + 1)
-                                .unwrap()
-                                == 'S'
-                                && input
-                                    .lines()
-                                    .nth(i + 1)
-                                    .unwrap()
-                                    .chars()
-                                    .nth(j - 1)
-                                    .unwrap()
-                                    == 'M'))
-                })
-                .count()
+impl Template {
+    pub fn parse(text: &str) -> Result<Template, String> {
+        Ok(Template {
+            cells: parse::grid(text)?,
         })
+    }
+
+    /// The template under every rotation and reflection, skipping any which
+    /// look the same as one already listed
+    fn orientations(&self) -> Vec<(Transform, Vec<Vec<char>>)> {
+        let mut orientations: Vec<(Transform, Vec<Vec<char>>)> = vec![];
+        for mirrored in [false, true] {
+            let mut cells = self.cells.clone();
+            if mirrored {
+                cells.iter_mut().for_each(|row| row.reverse());
+            }
+            for rotation in [0, 90, 180, 270] {
+                if !orientations.iter().any(|(_, seen)| *seen == cells) {
+                    let transform = Transform { rotation, mirrored };
+                    orientations.push((transform, cells.clone()));
+                }
+                cells = rotate(&cells);
+            }
+        }
+        orientations
+    }
+
+    /// Every place the template matches the grid, turned or flipped any way
+    pub fn find(&self, grid: &[Vec<char>]) -> Vec<Placement> {
+        let mut placements = vec![];
+        for (transform, cells) in self.orientations() {
+            let (width, height) = (cells[0].len(), cells.len());
+            for y in 0..(grid.len() + 1).saturating_sub(height) {
+                for x in 0..(grid[y].len() + 1).saturating_sub(width) {
+                    let matches = cells.iter().enumerate().all(|(dy, row)| {
+                        row.iter()
+                            .enumerate()
+                            .all(|(dx, ch)| *ch == WILDCARD || grid[y + dy][x + dx] == *ch)
+                    });
+                    if matches {
+                        placements.push(Placement { x, y, transform });
+                    }
+                }
+            }
+        }
+        placements
+    }
 }
 
-pub fn day4(input: String) -> Day4 {
-    let part1 = word_search(input.to_owned());
-    let part2 = xmas(input.to_owned());
+pub(crate) fn parse_input(input: &str) -> Result<Vec<Vec<char>>, String> {
+    parse::grid(input)
+}
+
+pub fn day4(input: String) -> Result<Day4, String> {
+    let grid = parse_input(&input)?;
+    let part1 = word_search(&grid, &["XMAS"]).len();
+    let part2 = Template::parse(X_MAS)?.find(&grid).len();
 
-    Day4 { part1, part2 }
+    Ok(Day4 { part1, part2 })
 }
 
 #[cfg(test)]
@@ -227,7 +217,7 @@ SAXAMASAAA
 MAMMMXMMMM
 MXMXAXMASX";
 
-        let result = day4(input.to_owned());
+        let result = day4(input.to_owned()).unwrap();
 
         assert_eq!(result.part1, 18);
     }
@@ -245,8 +235,66 @@ SAXAMASAAA
 MAMMMXMMMM
 MXMXAXMASX";
 
-        let result = day4(input.to_owned());
+        let result = day4(input.to_owned()).unwrap();
 
         assert_eq!(result.part2, 9);
     }
+
+    #[test]
+    fn finds_words_in_every_direction() {
+        let grid = parse::grid("CAT\nOXA\nWOC").unwrap();
+
+        let matches = word_search(&grid, &["CAT", "COW", "OX", "", "X", "CAT"]);
+
+        let found = |word, x, y, direction| Match {
+            word,
+            x,
+            y,
+            direction,
+        };
+        assert_eq!(
+            matches,
+            vec![
+                found("CAT", 0, 0, Direction::East),
+                found("COW", 0, 0, Direction::South),
+                found("OX", 0, 1, Direction::East),
+                found("X", 1, 1, Direction::East),
+                found("OX", 1, 2, Direction::North),
+                found("CAT", 2, 2, Direction::North),
+                found("COW", 2, 2, Direction::West),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_templates_turned_any_way() {
+        let grid = parse::grid("AB.\nCBA\n..C").unwrap();
+        let corner = Template::parse("AB\nC.").unwrap();
+
+        let placements = corner.find(&grid);
+
+        assert_eq!(
+            placements,
+            vec![
+                Placement {
+                    x: 0,
+                    y: 0,
+                    transform: Transform {
+                        rotation: 0,
+                        mirrored: false,
+                    },
+                },
+                Placement {
+                    x: 1,
+                    y: 1,
+                    transform: Transform {
+                        rotation: 0,
+                        mirrored: true,
+                    },
+                },
+            ]
+        );
+        assert_eq!(corner.orientations().len(), 8);
+        assert_eq!(Template::parse(X_MAS).unwrap().orientations().len(), 4);
+    }
 }
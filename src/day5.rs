@@ -1,71 +1,304 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use crate::explain::Explanation;
+use crate::parse;
+
 pub struct Day5 {
-    pub part1: i16,
-    pub part2: i16,
+    pub part1: Page,
+    pub part2: Page,
 }
 
-pub fn day5(input: String) -> Day5 {
-    let mut part1 = 0;
-    let mut part2 = 0;
+pub type Page = u64;
+
+type Ordering = Vec<(Page, Page)>;
 
+pub(crate) fn parse_input(input: &str) -> Result<(Ordering, Vec<Vec<Page>>), String> {
     let ordering = input
         .lines()
-        .take_while(|line| line.trim().len() > 0)
-        .map(|line| {
-            let order: Vec<i16> = line
-                .splitn(2, '|')
-                .map(|c| str::parse::<i16>(c).unwrap())
-                .collect();
-
-            (order[0], order[1])
+        .enumerate()
+        .take_while(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            parse::on_line(i, {
+                parse::split(line, "|")
+                    .and_then(|(a, b)| Ok((parse::number(a)?, parse::number(b)?)))
+            })
         })
-        .collect::<Vec<(i16, i16)>>();
+        .collect::<Result<Ordering, String>>()?;
 
-    let bubble_sort_pages_pass = |pages: &mut Vec<i16>| {
-        let mut has_changed = false;
-        for i in 0..pages.len() - 1 {
-            if ordering
-                .iter()
-                .any(|(a, b)| (*a == pages[i + 1] && *b == pages[i]))
-            {
-                pages[i..i + 2].rotate_left(1);
-                has_changed = true;
+    let updates = input
+        .lines()
+        .enumerate()
+        .skip(ordering.len() + 1)
+        .map(|(i, line)| {
+            parse::on_line(i, {
+                let pages = line
+                    .split(',')
+                    .map(parse::number::<Page>)
+                    .collect::<Result<Vec<Page>, String>>()?;
+                let mut seen = HashSet::new();
+                match pages.iter().find(|page| !seen.insert(**page)) {
+                    Some(page) => Err(format!("Page {} appears twice", page)),
+                    None => Ok(pages),
+                }
+            })
+        })
+        .collect::<Result<Vec<Vec<Page>>, String>>()?;
+
+    Ok((ordering, updates))
+}
+
+/// For each page, the pages which the rules say must come after it
+type Graph = HashMap<Page, HashSet<Page>>;
+
+fn precedence_graph(ordering: &Ordering) -> Graph {
+    let mut graph: Graph = HashMap::new();
+    for (before, after) in ordering {
+        graph.entry(*before).or_default().insert(*after);
+    }
+    graph
+}
+
+fn pages_after<'a>(graph: &'a Graph, page: &Page) -> impl Iterator<Item = &'a Page> {
+    graph.get(page).into_iter().flatten()
+}
+
+/// Whether the update follows every rule between its pages
+fn in_order(graph: &Graph, pages: &[Page]) -> bool {
+    let positions = pages
+        .iter()
+        .enumerate()
+        .map(|(i, page)| (*page, i))
+        .collect::<HashMap<Page, usize>>();
+    pages.iter().enumerate().all(|(i, page)| {
+        pages_after(graph, page).all(|after| positions.get(after).is_none_or(|j| *j > i))
+    })
+}
+
+/// A chain of rules among `pages` which leads back to where it started. Each
+/// of the pages must have another of them which comes before it.
+fn find_cycle(graph: &Graph, pages: &HashSet<Page>) -> Vec<Page> {
+    let before = |page: Page| {
+        pages
+            .iter()
+            .copied()
+            .filter(|other| pages_after(graph, other).any(|after| *after == page))
+            .min()
+    };
+
+    let mut path: Vec<Page> = vec![];
+    let mut seen: HashMap<Page, usize> = HashMap::new();
+    let mut page = pages.iter().copied().min();
+    while let Some(p) = page {
+        if let Some(start) = seen.get(&p) {
+            let mut cycle = path[*start..].to_vec();
+            cycle.reverse();
+            // start from the lowest page so the same cycle reads the same way
+            let lowest = (0..cycle.len()).min_by_key(|i| cycle[*i]).unwrap_or(0);
+            cycle.rotate_left(lowest);
+            cycle.push(cycle[0]);
+            return cycle;
+        }
+        seen.insert(p, path.len());
+        path.push(p);
+        page = before(p);
+    }
+    path
+}
+
+/// The update's pages reordered to follow the rules between them, keeping
+/// pages in their original order wherever the rules allow
+fn topological_order(graph: &Graph, pages: &[Page]) -> Result<Vec<Page>, String> {
+    let positions = pages
+        .iter()
+        .enumerate()
+        .map(|(i, page)| (*page, i))
+        .collect::<HashMap<Page, usize>>();
+    let mut pages_before = vec![0; pages.len()];
+    for page in pages {
+        for after in pages_after(graph, page) {
+            if let Some(j) = positions.get(after) {
+                pages_before[*j] += 1;
             }
         }
-        has_changed
-    };
+    }
 
-    let bubble_sort_pages = |pages: &mut Vec<i16>| {
-        let mut has_sorted = false;
-        let mut num_passes = 0;
-        while !has_sorted {
-            has_sorted = !bubble_sort_pages_pass(pages);
-            num_passes += 1;
+    let mut ready = (0..pages.len())
+        .filter(|i| pages_before[*i] == 0)
+        .map(Reverse)
+        .collect::<BinaryHeap<Reverse<usize>>>();
+    let mut ordered = Vec::with_capacity(pages.len());
+    while let Some(Reverse(i)) = ready.pop() {
+        ordered.push(pages[i]);
+        for after in pages_after(graph, &pages[i]) {
+            if let Some(j) = positions.get(after) {
+                pages_before[*j] -= 1;
+                if pages_before[*j] == 0 {
+                    ready.push(Reverse(*j));
+                }
+            }
         }
+    }
 
-        num_passes > 1
-    };
+    if ordered.len() < pages.len() {
+        let stuck = (0..pages.len())
+            .filter(|i| pages_before[*i] > 0)
+            .map(|i| pages[i])
+            .collect::<HashSet<Page>>();
+        let cycle = find_cycle(graph, &stuck)
+            .iter()
+            .map(|page| page.to_string())
+            .collect::<Vec<String>>();
+        return Err(format!("Rules form a cycle: {}", cycle.join(" -> ")));
+    }
+    Ok(ordered)
+}
 
-    input
-        .lines()
-        .skip(ordering.len() + 1)
-        .map(|line| {
-            line.split(',')
-                .map(|c| str::parse::<i16>(c).unwrap())
-                .collect::<Vec<i16>>()
-        })
-        .for_each(|pages| {
-            let mut ordered_pages = pages.clone();
-            let sorted = bubble_sort_pages(&mut ordered_pages);
-
-            if sorted {
-                part2 += ordered_pages[pages.len() / 2];
-            } else {
-                part1 += pages[pages.len() / 2];
+pub fn day5(input: String) -> Result<Day5, String> {
+    let mut part1: Page = 0;
+    let mut part2: Page = 0;
+
+    let (ordering, updates) = parse_input(&input)?;
+    let graph = precedence_graph(&ordering);
+
+    let too_large = || "Sum of middle pages is too large".to_owned();
+    for pages in updates {
+        if in_order(&graph, &pages) {
+            part1 = part1
+                .checked_add(pages[pages.len() / 2])
+                .ok_or_else(too_large)?;
+        } else {
+            let ordered_pages = topological_order(&graph, &pages)?;
+            part2 = part2
+                .checked_add(ordered_pages[pages.len() / 2])
+                .ok_or_else(too_large)?;
+        }
+    }
+
+    Ok(Day5 { part1, part2 })
+}
+
+/// A rule an update breaks, by putting `before` after `after`. Positions
+/// are 0-based.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Violation {
+    pub before: Page,
+    pub after: Page,
+    pub positions: (usize, usize),
+}
+
+/// Every rule the update breaks, in the order of the later page
+fn violations(graph: &Graph, pages: &[Page]) -> Vec<Violation> {
+    let mut violations = vec![];
+    for (j, before) in pages.iter().enumerate() {
+        for (i, after) in pages[..j].iter().enumerate() {
+            if pages_after(graph, before).any(|page| page == after) {
+                violations.push(Violation {
+                    before: *before,
+                    after: *after,
+                    positions: (j, i),
+                });
             }
-        });
+        }
+    }
+    violations
+}
+
+/// The rules each out of order update breaks, and how it's reordered
+pub fn explain(input: String) -> Result<Vec<Explanation>, String> {
+    let (ordering, updates) = parse_input(&input)?;
+    let graph = precedence_graph(&ordering);
+    let join = |pages: &[Page]| {
+        pages
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<Vec<String>>()
+            .join(",")
+    };
+
+    let mut explanations = vec![];
+    for (n, pages) in updates.iter().enumerate() {
+        let subject = format!("update {} ({})", n + 1, join(pages));
+        for v in violations(&graph, pages) {
+            explanations.push(Explanation::new(
+                1,
+                subject.clone(),
+                format!(
+                    "breaks {}|{}: {} is at position {} but {} is at position {}",
+                    v.before,
+                    v.after,
+                    v.before,
+                    v.positions.0 + 1,
+                    v.after,
+                    v.positions.1 + 1
+                ),
+            ));
+        }
+        if !in_order(&graph, pages) {
+            let ordered = topological_order(&graph, pages)?;
+            explanations.push(Explanation::new(
+                2,
+                subject,
+                format!(
+                    "reordered to {}, middle page {}",
+                    join(&ordered),
+                    ordered[ordered.len() / 2]
+                ),
+            ));
+        }
+    }
+    Ok(explanations)
+}
+
+/// The ordering rules as a Graphviz graph, with an edge from each page to
+/// the pages which must come after it
+pub fn to_dot(input: String) -> Result<String, String> {
+    let (ordering, _) = parse_input(&input)?;
+    let mut dot = String::from("digraph rules {\n");
+    for (before, after) in ordering {
+        dot += &format!("  {} -> {};\n", before, after);
+    }
+    dot += "}\n";
+    Ok(dot)
+}
+
+/// The ordering rules grouped by the page which must come first, followed by
+/// the updates
+pub fn describe(input: String) -> Result<String, String> {
+    let (ordering, updates) = parse_input(&input)?;
+
+    let mut before = ordering.iter().map(|(a, _)| *a).collect::<Vec<Page>>();
+    before.sort();
+    before.dedup();
+
+    let rules = before.iter().map(|a| {
+        let after = ordering
+            .iter()
+            .filter(|(x, _)| x == a)
+            .map(|(_, b)| b.to_string())
+            .collect::<Vec<String>>();
+        format!("  {} before {}", a, after.join(", "))
+    });
+
+    let updates_described = updates.iter().map(|pages| {
+        format!(
+            "  {}",
+            pages
+                .iter()
+                .map(|p| p.to_string())
+                .collect::<Vec<String>>()
+                .join(",")
+        )
+    });
 
-    Day5 { part1, part2 }
+    Ok(std::iter::once(format!("{} rules:", ordering.len()))
+        .chain(rules)
+        .chain(std::iter::once(format!("{} updates:", updates.len())))
+        .chain(updates_described)
+        .collect::<Vec<String>>()
+        .join("\n"))
 }
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -101,7 +334,7 @@ mod tests {
 61,13,29
 97,13,75,29,47";
 
-        let result = day5(input.to_owned());
+        let result = day5(input.to_owned()).unwrap();
 
         assert_eq!(result.part1, 143);
     }
@@ -137,8 +370,52 @@ mod tests {
 61,13,29
 97,13,75,29,47";
 
-        let result = day5(input.to_owned());
+        let result = day5(input.to_owned()).unwrap();
 
         assert_eq!(result.part2, 123);
     }
+
+    #[test]
+    fn reports_rule_cycles() {
+        let input = r"1|2
+2|3
+3|1
+4|1
+
+4,3,2,1";
+
+        let result = day5(input.to_owned());
+
+        assert_eq!(
+            result.err(),
+            Some("Rules form a cycle: 1 -> 2 -> 3 -> 1".to_owned())
+        );
+    }
+
+    #[test]
+    fn lists_violated_rules() {
+        let input = r"47|53
+97|75
+75|47
+97|47
+
+75,97,47,53
+100000,47";
+
+        let (ordering, updates) = parse_input(input).unwrap();
+        let graph = precedence_graph(&ordering);
+
+        assert_eq!(
+            violations(&graph, &updates[0]),
+            vec![Violation {
+                before: 97,
+                after: 75,
+                positions: (1, 0),
+            }]
+        );
+        assert!(violations(&graph, &updates[1]).is_empty());
+        assert!(to_dot(input.to_owned())
+            .unwrap()
+            .starts_with("digraph rules {\n  47 -> 53;\n  97 -> 75;\n"));
+    }
 }
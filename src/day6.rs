@@ -1,5 +1,9 @@
 use std::collections::HashSet;
 
+use crate::parse;
+use crate::player::Animation;
+use crate::render::{Render, Tile, BLACK, GREY, RED, YELLOW};
+
 pub struct Day6 {
     pub part1: usize,
     pub part2: usize,
@@ -10,185 +14,258 @@ const RIGHT: i8 = 1;
 const DOWN: i8 = 2;
 const LEFT: i8 = 3;
 
-fn init(input: &str) -> (usize, usize, (usize, usize), i8) {
-    let pos_line = input
-        .lines()
-        .enumerate()
-        .skip_while(|(_, line)| !line.contains('^'))
-        .next()
-        .unwrap();
-    let pos_col = pos_line
-        .1
-        .chars()
-        .enumerate()
-        .find(|(_, ch)| *ch == '^')
-        .unwrap();
+/// The lab map, with where the guard starts
+pub(crate) struct Map {
+    cells: Vec<Vec<char>>,
+    start: (usize, usize),
+}
 
-    let height = input.lines().count();
-    let width = input.lines().next().unwrap().len();
+pub(crate) fn init(input: &str) -> Result<Map, String> {
+    let cells = parse::grid(input)?;
+    parse::only_chars(&cells, ".#^")?;
+    let start = parse::find_one(&cells, '^')?;
+    Ok(Map { cells, start })
+}
+
+impl Map {
+    fn width(&self) -> usize {
+        self.cells[0].len()
+    }
 
-    let pos = (pos_col.0, pos_line.0);
-    let direction = UP;
+    fn height(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// The position one step ahead, unless that's off the map
+    fn ahead(&self, (x, y): (usize, usize), direction: i8) -> Option<(usize, usize)> {
+        match direction {
+            UP => y.checked_sub(1).map(|y| (x, y)),
+            RIGHT => (x + 1 < self.width()).then_some((x + 1, y)),
+            DOWN => (y + 1 < self.height()).then_some((x, y + 1)),
+            _ => x.checked_sub(1).map(|x| (x, y)),
+        }
+    }
 
-    (height, width, pos, direction)
+    fn is_obstacle(&self, (x, y): (usize, usize)) -> bool {
+        self.cells[y][x] == '#'
+    }
 }
 
-fn crawl(input: String) -> HashSet<(usize, usize)> {
-    let (height, width, mut pos, mut direction) = init(&input);
+/// Where the guard is and which way they face
+type State = ((usize, usize), i8);
 
-    let mut positions = HashSet::new();
-    positions.insert(pos);
+/// Every state of the guard, in order, from the start until they leave the map
+fn walk(map: &Map) -> Result<Vec<State>, String> {
+    let mut pos = map.start;
+    let mut direction = UP;
 
-    loop {
-        let next_position = match direction {
-            UP => {
-                if pos.1 == 0 {
-                    None
-                } else {
-                    Some((pos.0, pos.1 - 1))
-                }
-            }
-            RIGHT => {
-                if pos.0 == width - 1 {
-                    None
-                } else {
-                    Some((pos.0 + 1, pos.1))
-                }
-            }
-            DOWN => {
-                if pos.1 == height - 1 {
-                    None
-                } else {
-                    Some((pos.0, pos.1 + 1))
-                }
-            }
-            LEFT => {
-                if pos.0 == 0 {
-                    None
-                } else {
-                    Some((pos.0 - 1, pos.1))
-                }
-            }
-            _ => panic!("invalid direction"),
-        };
+    let mut states = vec![(pos, direction)];
+    let mut seen = HashSet::from([(pos, direction)]);
 
-        if let Some(pos_next) = next_position {
-            let char_next = input
-                .lines()
-                .nth(pos_next.1)
-                .unwrap()
-                .chars()
-                .nth(pos_next.0)
-                .unwrap();
-
-            if char_next == '#' {
-                direction = (direction + 1) % 4;
-            } else {
-                positions.insert(pos_next);
-                pos = pos_next;
-            }
+    while let Some(pos_next) = map.ahead(pos, direction) {
+        if map.is_obstacle(pos_next) {
+            direction = (direction + 1) % 4;
         } else {
-            break;
+            pos = pos_next;
+        }
+        if !seen.insert((pos, direction)) {
+            return Err("The guard walks in a loop and never leaves".to_owned());
         }
+        states.push((pos, direction));
     }
 
-    positions
+    Ok(states)
 }
 
-fn part1(input: String) -> usize {
-    crawl(input).len()
+fn crawl(map: &Map) -> Result<HashSet<(usize, usize)>, String> {
+    Ok(walk(map)?.into_iter().map(|(pos, _)| pos).collect())
 }
 
-fn part2(input: String) -> usize {
-    let visited_init = crawl(input.clone());
-
-    let (height, width, position, direction) = init(&input);
+pub(crate) fn part1(input: String) -> Result<usize, String> {
+    Ok(crawl(&init(&input)?)?.len())
+}
 
-    let check_obstruction = |obstruction: (usize, usize)| {
-        let mut vector = (position.0, position.1, direction);
-        let mut visited = HashSet::new();
+/// For every cell and direction, the first obstacle the guard would walk
+/// into, so they can jump straight from one turn to the next
+struct Jumps<'a> {
+    map: &'a Map,
+    obstacles: Vec<[Option<(usize, usize)>; 4]>,
+}
 
-        visited.insert(vector);
+impl<'a> Jumps<'a> {
+    fn new(map: &'a Map) -> Self {
+        let (width, height) = (map.width(), map.height());
+        let mut obstacles = vec![[None; 4]; width * height];
+        let mut sweep = |cells: &mut dyn Iterator<Item = (usize, usize)>, direction: i8| {
+            let mut obstacle = None;
+            for (x, y) in cells {
+                if map.is_obstacle((x, y)) {
+                    obstacle = Some((x, y));
+                } else {
+                    obstacles[y * width + x][direction as usize] = obstacle;
+                }
+            }
+        };
+        for x in 0..width {
+            sweep(&mut (0..height).map(|y| (x, y)), UP);
+            sweep(&mut (0..height).rev().map(|y| (x, y)), DOWN);
+        }
+        for y in 0..height {
+            sweep(&mut (0..width).map(|x| (x, y)), LEFT);
+            sweep(&mut (0..width).rev().map(|x| (x, y)), RIGHT);
+        }
+        Jumps { map, obstacles }
+    }
 
-        let mut is_obstructed = false;
+    /// The first obstacle ahead of `pos`, counting the `extra` one
+    fn next_obstacle(
+        &self,
+        (x, y): (usize, usize),
+        direction: i8,
+        extra: (usize, usize),
+    ) -> Option<(usize, usize)> {
+        let found = self.obstacles[y * self.map.width() + x][direction as usize];
+        let (ex, ey) = extra;
+        let extra_first = match direction {
+            UP => ex == x && ey < y && found.is_none_or(|(_, oy)| ey > oy),
+            RIGHT => ey == y && ex > x && found.is_none_or(|(ox, _)| ex < ox),
+            DOWN => ex == x && ey > y && found.is_none_or(|(_, oy)| ey < oy),
+            _ => ey == y && ex < x && found.is_none_or(|(ox, _)| ex > ox),
+        };
+        if extra_first {
+            Some(extra)
+        } else {
+            found
+        }
+    }
 
-        loop {
-            let next_position = match vector.2 {
-                UP => {
-                    if vector.1 == 0 {
-                        None
-                    } else {
-                        Some((vector.0, vector.1 - 1))
-                    }
-                }
-                RIGHT => {
-                    if vector.0 == width - 1 {
-                        None
-                    } else {
-                        Some((vector.0 + 1, vector.1))
-                    }
-                }
-                DOWN => {
-                    if vector.1 == height - 1 {
-                        None
-                    } else {
-                        Some((vector.0, vector.1 + 1))
-                    }
-                }
-                LEFT => {
-                    if vector.0 == 0 {
-                        None
-                    } else {
-                        Some((vector.0 - 1, vector.1))
-                    }
-                }
-                _ => panic!("invalid direction"),
+    /// Whether the guard walks in a loop once `obstruction` is added. `seen`
+    /// marks the turns taken with `stamp`, so it can be reused between calls
+    /// without clearing.
+    fn loops(&self, obstruction: (usize, usize), seen: &mut [u32], stamp: u32) -> bool {
+        let mut pos = self.map.start;
+        let mut direction = UP;
+        while let Some((x, y)) = self.next_obstacle(pos, direction, obstruction) {
+            pos = match direction {
+                UP => (x, y + 1),
+                RIGHT => (x - 1, y),
+                DOWN => (x, y - 1),
+                _ => (x + 1, y),
             };
-
-            if let Some(pos_next) = next_position {
-                let char_next = input
-                    .lines()
-                    .nth(pos_next.1)
-                    .unwrap()
-                    .chars()
-                    .nth(pos_next.0)
-                    .unwrap();
-
-                let vector_next = (pos_next.0, pos_next.1, vector.2);
-
-                if pos_next == obstruction || char_next == '#' {
-                    vector.2 = (vector.2 + 1) % 4;
-                } else if visited.contains(&vector_next) {
-                    is_obstructed = true;
-                    break;
-                } else {
-                    vector = vector_next;
-                    visited.insert(vector);
-                }
-            } else {
-                break;
+            direction = (direction + 1) % 4;
+            let turn = (pos.1 * self.map.width() + pos.0) * 4 + direction as usize;
+            if seen[turn] == stamp {
+                return true;
             }
+            seen[turn] = stamp;
         }
+        false
+    }
+}
 
-        is_obstructed
-    };
+pub(crate) fn part2(input: String) -> Result<usize, String> {
+    let map = init(&input)?;
+    // an obstruction can't go where the guard starts
+    let candidates = crawl(&map)?
+        .into_iter()
+        .filter(|p| *p != map.start)
+        .collect::<Vec<(usize, usize)>>();
+    let jumps = Jumps::new(&map);
+    let cells = map.width() * map.height();
+
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk_size = candidates.len().div_ceil(threads).max(1);
+    std::thread::scope(|scope| {
+        let searches = candidates
+            .chunks(chunk_size)
+            .map(|chunk| {
+                let jumps = &jumps;
+                scope.spawn(move || {
+                    let mut seen = vec![0; cells * 4];
+                    (1..)
+                        .zip(chunk)
+                        .filter(|(stamp, obstruction)| {
+                            jumps.loops(**obstruction, &mut seen, *stamp)
+                        })
+                        .count()
+                })
+            })
+            .collect::<Vec<_>>();
+        searches.into_iter().try_fold(0, |sum, search| {
+            let count = search
+                .join()
+                .map_err(|_| "Obstruction search failed".to_owned())?;
+            Ok(sum + count)
+        })
+    })
+}
+
+pub struct GuardWalk {
+    cells: Vec<Vec<char>>,
+    states: Vec<State>,
+}
 
-    visited_init.iter().skip(1).fold(0, |sum, position| {
-        if check_obstruction(*position) {
-            sum + 1
+struct GuardFrame<'a> {
+    walk: &'a GuardWalk,
+    visited: HashSet<(usize, usize)>,
+    guard: State,
+}
+
+impl Render for GuardFrame<'_> {
+    fn size(&self) -> (usize, usize) {
+        (self.walk.cells[0].len(), self.walk.cells.len())
+    }
+
+    fn tile(&self, x: usize, y: usize) -> Tile {
+        let (position, direction) = self.guard;
+        if position == (x, y) {
+            let ch = match direction {
+                UP => '^',
+                RIGHT => '>',
+                DOWN => 'v',
+                _ => '<',
+            };
+            Tile::new(ch, RED)
+        } else if self.walk.cells[y][x] == '#' {
+            Tile::new('#', GREY)
+        } else if self.visited.contains(&(x, y)) {
+            Tile::new('X', YELLOW)
         } else {
-            sum
+            Tile::new('.', BLACK)
         }
-    })
+    }
 }
 
-pub fn day6(input: String) -> Day6 {
-    Day6 {
-        part1: part1(input.clone()),
-        part2: part2(input.clone()),
+impl Animation for GuardWalk {
+    fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    fn frame(&self, step: usize) -> Box<dyn Render + '_> {
+        Box::new(GuardFrame {
+            walk: self,
+            visited: self.states[..=step].iter().map(|(pos, _)| *pos).collect(),
+            guard: self.states[step],
+        })
     }
 }
 
+/// The guard's patrol, one frame per move or turn
+pub fn animation(input: String) -> Result<GuardWalk, String> {
+    let map = init(&input)?;
+    Ok(GuardWalk {
+        states: walk(&map)?,
+        cells: map.cells,
+    })
+}
+
+pub fn day6(input: String) -> Result<Day6, String> {
+    Ok(Day6 {
+        part1: part1(input.clone())?,
+        part2: part2(input.clone())?,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -206,7 +283,7 @@ mod tests {
 #.........
 ......#...";
 
-        let result = day6(input.to_owned());
+        let result = day6(input.to_owned()).unwrap();
 
         assert_eq!(result.part1, 41);
     }
@@ -224,8 +301,20 @@ mod tests {
 #.........
 ......#...";
 
-        let result = day6(input.to_owned());
+        let result = day6(input.to_owned()).unwrap();
 
         assert_eq!(result.part2, 6);
     }
+
+    #[test]
+    fn animates_guard_walk() {
+        let input = r".#..
+....
+.^..";
+        let animation = animation(input.to_owned()).unwrap();
+        assert_eq!(animation.len(), 5);
+        assert_eq!(animation.frame(0).to_text(), ".#..\n....\n.^..");
+        assert_eq!(animation.frame(2).to_text(), ".#..\n.>..\n.X..");
+        assert_eq!(animation.frame(4).to_text(), ".#..\n.XX>\n.X..");
+    }
 }
@@ -1,3 +1,6 @@
+use crate::explain::Explanation;
+use crate::parse;
+
 pub struct Day7 {
     pub part1: i64,
     pub part2: i64,
@@ -8,100 +11,134 @@ const OP_TIMES: i64 = 1;
 
 const OP_CONCAT: i64 = 2;
 
-fn part1(input: String) -> i64 {
-    input.lines().fold(0, |sum, line| {
-        let (result_str, inputs_str) = line.split_once(':').unwrap();
-        let expected_result = str::parse::<i64>(result_str).unwrap();
+/// Beyond this, trying every sequence of operators takes far too long
+const MAX_INPUTS: usize = 16;
 
-        let inputs_int = inputs_str
-            .trim()
-            .split(' ')
-            .map(|c| str::parse::<i64>(c).unwrap());
+fn parse_equation(line: &str) -> Result<(i64, Vec<i64>), String> {
+    let (result_str, inputs_str) = parse::split(line, ":")?;
+    let expected_result = parse::number::<i64>(result_str)?;
 
-        let num_ops = (inputs_int.clone().count() - 1) as u32;
+    let inputs = inputs_str
+        .trim()
+        .split(' ')
+        .map(parse::number::<i64>)
+        .collect::<Result<Vec<i64>, String>>()?;
+    if inputs.len() > MAX_INPUTS {
+        return Err(format!("More than {} numbers", MAX_INPUTS));
+    }
 
-        let num_permutations = 2_i64.pow(num_ops);
+    Ok((expected_result, inputs))
+}
 
-        let valid_permutation = (0..num_permutations).into_iter().find(|i| {
-            let mut inputs = inputs_int.clone();
-            let result_with_op_sequence = (0..num_ops)
-                .fold((*i, inputs.next().unwrap().clone()), |(n, result), _j| {
-                    let op = n.rem_euclid(2);
-                    let n_next = (n - op) / 2;
+pub(crate) fn parse_input(input: &str) -> Result<Vec<(i64, Vec<i64>)>, String> {
+    parse::lines(input, parse_equation)
+}
 
-                    let input = inputs.next().unwrap();
-                    let result_next = match op {
-                        OP_PLUS => result + input,
-                        OP_TIMES => result * input,
-                        _ => panic!("Invalid op {}", op),
-                    };
+fn apply(op: i64, left: i64, right: i64) -> Option<i64> {
+    match op {
+        OP_PLUS => left.checked_add(right),
+        OP_TIMES => left.checked_mul(right),
+        OP_CONCAT => str::parse::<i64>(format!("{}{}", left, right).as_str()).ok(),
+        _ => panic!("Invalid op {}", op),
+    }
+}
 
-                    (n_next, result_next)
+/// Finds a sequence of operators (the first `num_op_kinds` of plus, times and
+/// concat) which combines the inputs left-to-right into the expected result.
+fn find_operators(expected_result: i64, inputs: &[i64], num_op_kinds: i64) -> Option<Vec<i64>> {
+    let num_ops = (inputs.len() - 1) as u32;
+
+    let num_permutations = num_op_kinds.pow(num_ops);
+
+    // each permutation index holds its operators as base `num_op_kinds`
+    // digits, lowest first
+    let ops_from_permutation = |i: i64| {
+        (0..num_ops)
+            .scan(i, |n, _| {
+                let op = n.rem_euclid(num_op_kinds);
+                *n /= num_op_kinds;
+                Some(op)
+            })
+            .collect::<Vec<i64>>()
+    };
+
+    // a sequence which overflows can't reach the expected result
+    (0..num_permutations)
+        .find(|i| {
+            let result_with_op_sequence = inputs[1..]
+                .iter()
+                .try_fold((*i, inputs[0]), |(n, result), input| {
+                    let op = n.rem_euclid(num_op_kinds);
+                    Some((n / num_op_kinds, apply(op, result, *input)?))
                 })
-                .1;
-
-            result_with_op_sequence == expected_result
-        });
+                .map(|(_, result)| result);
 
-        if valid_permutation.is_some() {
-            sum + expected_result
-        } else {
-            sum
-        }
-    })
+            result_with_op_sequence == Some(expected_result)
+        })
+        .map(ops_from_permutation)
 }
 
-fn part2(input: String) -> i64 {
-    input.lines().fold(0, |sum, line| {
-        let (result_str, inputs_str) = line.split_once(':').unwrap();
-        let expected_result = str::parse::<i64>(result_str).unwrap();
-
-        let inputs_int = inputs_str
-            .trim()
-            .split(' ')
-            .map(|c| str::parse::<i64>(c).unwrap());
-
-        let num_ops = (inputs_int.clone().count() - 1) as u32;
-
-        let num_permutations = 3_i64.pow(num_ops);
-
-        let valid_permutation = (0..num_permutations).into_iter().find(|i| {
-            let mut inputs = inputs_int.clone();
-            let result_with_op_sequence = (0..num_ops)
-                .fold((*i, inputs.next().unwrap().clone()), |(n, result), _j| {
-                    let op = n.rem_euclid(3);
-                    let n_next = (n - op) / 3;
-
-                    let input = inputs.next().unwrap();
-                    let result_next = match op {
-                        OP_PLUS => result + input,
-                        OP_TIMES => result * input,
-                        OP_CONCAT => {
-                            str::parse::<i64>(format!("{}{}", result, input).as_str()).unwrap()
-                        }
-                        _ => panic!("Invalid op {}", op),
-                    };
+fn calibration_result(input: String, num_op_kinds: i64) -> Result<i64, String> {
+    parse_input(&input)?
+        .iter()
+        .try_fold(0_i64, |sum, (expected_result, inputs)| {
+            if find_operators(*expected_result, inputs, num_op_kinds).is_some() {
+                sum.checked_add(*expected_result)
+                    .ok_or_else(|| "Calibration result is too large".to_owned())
+            } else {
+                Ok(sum)
+            }
+        })
+}
 
-                    (n_next, result_next)
-                })
-                .1;
+pub(crate) fn part1(input: String) -> Result<i64, String> {
+    calibration_result(input, 2)
+}
 
-            result_with_op_sequence == expected_result
-        });
+pub(crate) fn part2(input: String) -> Result<i64, String> {
+    calibration_result(input, 3)
+}
 
-        if valid_permutation.is_some() {
-            sum + expected_result
-        } else {
-            sum
-        }
+pub fn day7(input: String) -> Result<Day7, String> {
+    Ok(Day7 {
+        part1: part1(input.clone())?,
+        part2: part2(input.clone())?,
     })
 }
 
-pub fn day7(input: String) -> Day7 {
-    Day7 {
-        part1: part1(input.clone()),
-        part2: part2(input.clone()),
-    }
+fn format_equation(expected_result: i64, inputs: &[i64], ops: &[i64]) -> String {
+    ops.iter().zip(inputs.iter().skip(1)).fold(
+        format!("{} = {}", expected_result, inputs[0]),
+        |equation, (op, input)| {
+            let symbol = match *op {
+                OP_PLUS => "+",
+                OP_TIMES => "*",
+                OP_CONCAT => "||",
+                _ => panic!("Invalid op {}", op),
+            };
+            format!("{} {} {}", equation, symbol, input)
+        },
+    )
+}
+
+pub fn explain(input: String) -> Result<Vec<Explanation>, String> {
+    let equations = parse_input(&input)?;
+    Ok(input
+        .lines()
+        .zip(equations)
+        .flat_map(|(line, (expected_result, inputs))| {
+            [(1, 2), (2, 3)]
+                .into_iter()
+                .map(|(part, num_op_kinds)| {
+                    let detail = match find_operators(expected_result, &inputs, num_op_kinds) {
+                        Some(ops) => format_equation(expected_result, &inputs, &ops),
+                        None => "no operators match".to_owned(),
+                    };
+                    Explanation::new(part, line, detail)
+                })
+                .collect::<Vec<Explanation>>()
+        })
+        .collect())
 }
 
 #[cfg(test)]
@@ -120,7 +157,7 @@ mod tests {
 21037: 9 7 18 13
 292: 11 6 16 20";
 
-        let result = day7(input.to_owned());
+        let result = day7(input.to_owned()).unwrap();
 
         assert_eq!(result.part1, 3749);
     }
@@ -137,8 +174,29 @@ mod tests {
 21037: 9 7 18 13
 292: 11 6 16 20";
 
-        let result = day7(input.to_owned());
+        let result = day7(input.to_owned()).unwrap();
 
         assert_eq!(result.part2, 11387);
     }
+
+    #[test]
+    fn explains_matching_operators() {
+        let input = r"3267: 81 40 27
+83: 17 5
+7290: 6 8 6 15";
+
+        let explanations = explain(input.to_owned()).unwrap();
+
+        assert_eq!(
+            explanations,
+            vec![
+                Explanation::new(1, "3267: 81 40 27", "3267 = 81 * 40 + 27"),
+                Explanation::new(2, "3267: 81 40 27", "3267 = 81 * 40 + 27"),
+                Explanation::new(1, "83: 17 5", "no operators match"),
+                Explanation::new(2, "83: 17 5", "no operators match"),
+                Explanation::new(1, "7290: 6 8 6 15", "no operators match"),
+                Explanation::new(2, "7290: 6 8 6 15", "7290 = 6 * 8 || 6 * 15"),
+            ]
+        );
+    }
 }
@@ -1,5 +1,7 @@
 use std::collections::HashSet;
 
+use crate::parse;
+
 pub struct Day8 {
     pub part1: usize,
     pub part2: usize,
@@ -117,10 +119,15 @@ fn get_part2(input: String) -> usize {
         .len()
 }
 
-pub fn day8(input: String) -> Day8 {
+pub(crate) fn parse_input(input: &str) -> Result<Vec<Vec<char>>, String> {
+    parse::grid(input)
+}
+
+pub fn day8(input: String) -> Result<Day8, String> {
+    parse_input(&input)?;
     let part1 = get_part1(input.clone());
     let part2 = get_part2(input.clone());
-    Day8 { part1, part2 }
+    Ok(Day8 { part1, part2 })
 }
 
 #[cfg(test)]
@@ -142,7 +149,7 @@ mod tests {
 ............
 ............";
 
-        let result = day8(input.to_owned());
+        let result = day8(input.to_owned()).unwrap();
 
         assert_eq!(result.part1, 14);
     }
@@ -162,7 +169,7 @@ mod tests {
 ............
 ............";
 
-        let result = day8(input.to_owned());
+        let result = day8(input.to_owned()).unwrap();
 
         assert_eq!(result.part2, 34);
     }
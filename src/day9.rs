@@ -5,30 +5,47 @@ pub struct Day9 {
     pub part2: usize,
 }
 
-fn part1(input: String) -> usize {
+/// The length of each file and free space, alternating
+pub(crate) fn parse_input(input: &str) -> Result<Vec<usize>, String> {
+    input
+        .trim()
+        .chars()
+        .map(|ch| {
+            ch.to_digit(10)
+                .map(|n| n as usize)
+                .ok_or_else(|| format!("Invalid digit {:?}", ch))
+        })
+        .collect()
+}
+
+pub(crate) fn part1(input: String) -> Result<usize, String> {
     let mut blocks = vec![];
-    input.trim().chars().enumerate().for_each(|(i, ch)| {
-        let n = ch.to_digit(10).unwrap() as usize;
-        if i.rem_euclid(2) == 0 {
-            let block = i / 2;
-            (0..n).into_iter().for_each(|_| {
-                blocks.push(Some(block));
-            });
-        } else {
-            (0..n).into_iter().for_each(|_| {
-                blocks.push(None);
-            });
-        }
-    });
+    parse_input(&input)?
+        .into_iter()
+        .enumerate()
+        .for_each(|(i, n)| {
+            if i.rem_euclid(2) == 0 {
+                let block = i / 2;
+                (0..n).into_iter().for_each(|_| {
+                    blocks.push(Some(block));
+                });
+            } else {
+                (0..n).into_iter().for_each(|_| {
+                    blocks.push(None);
+                });
+            }
+        });
 
     let mut i = 0;
-    while i < blocks.len() - 1 {
+    while i < blocks.len() {
         if blocks[i].is_none() {
-            let mut next = blocks.pop().unwrap();
-            while next.is_none() {
-                next = blocks.pop().unwrap();
+            while blocks.last() == Some(&None) {
+                blocks.pop();
             }
-            blocks[i] = next;
+            if i >= blocks.len() {
+                break;
+            }
+            blocks[i] = blocks.pop().unwrap();
         }
         i += 1;
     }
@@ -38,17 +55,14 @@ fn part1(input: String) -> usize {
         .enumerate()
         .fold(0, |prev, (i, block)| prev + i * block.unwrap_or(0));
 
-    sum
+    Ok(sum)
 }
 
-fn part2(input: String) -> usize {
-    let mut blocks = input
-        .trim()
-        .chars()
+pub(crate) fn part2(input: String) -> Result<usize, String> {
+    let mut blocks = parse_input(&input)?
+        .into_iter()
         .enumerate()
-        .map(|(i, ch)| {
-            let n = ch.to_digit(10).unwrap() as usize;
-
+        .map(|(i, n)| {
             if i.rem_euclid(2) == 0 {
                 let id = i / 2;
                 (Some(id), n)
@@ -58,7 +72,7 @@ fn part2(input: String) -> usize {
         })
         .collect::<Vec<(Option<usize>, usize)>>();
 
-    let mut i = blocks.len() - 1;
+    let mut i = blocks.len().saturating_sub(1);
     let mut moved_blocks = HashSet::new();
 
     while i > 0 {
@@ -83,7 +97,7 @@ fn part2(input: String) -> usize {
         i -= 1;
     }
 
-    blocks
+    Ok(blocks
         .iter()
         .map(|block| {
             (0..block.1)
@@ -92,14 +106,14 @@ fn part2(input: String) -> usize {
         })
         .flatten()
         .enumerate()
-        .fold(0, |sum, (i, block)| sum + i * block)
+        .fold(0, |sum, (i, block)| sum + i * block))
 }
 
-pub fn day9(input: String) -> Day9 {
-    Day9 {
-        part1: part1(input.clone()),
-        part2: part2(input.clone()),
-    }
+pub fn day9(input: String) -> Result<Day9, String> {
+    Ok(Day9 {
+        part1: part1(input.clone())?,
+        part2: part2(input.clone())?,
+    })
 }
 
 #[cfg(test)]
@@ -110,7 +124,7 @@ mod tests {
     fn gets_part1() {
         let input = r"2333133121414131402";
 
-        let result = day9(input.to_owned());
+        let result = day9(input.to_owned()).unwrap();
 
         assert_eq!(result.part1, 1928);
     }
@@ -119,7 +133,7 @@ mod tests {
     fn gets_part2() {
         let input = r"2333133121414131402";
 
-        let result = day9(input.to_owned());
+        let result = day9(input.to_owned()).unwrap();
 
         assert_eq!(result.part2, 2858);
     }
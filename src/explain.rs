@@ -0,0 +1,64 @@
+/// One step of a solver's derivation, e.g. why a report was unsafe or which
+/// operators balanced an equation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Explanation {
+    pub part: u8,
+    pub subject: String,
+    pub detail: String,
+}
+
+impl Explanation {
+    pub fn new(part: u8, subject: impl Into<String>, detail: impl Into<String>) -> Self {
+        Explanation {
+            part,
+            subject: subject.into(),
+            detail: detail.into(),
+        }
+    }
+}
+
+pub fn render(explanations: &[Explanation]) -> String {
+    let mut lines = vec![];
+    for part in [1, 2] {
+        let records = explanations
+            .iter()
+            .filter(|e| e.part == part)
+            .collect::<Vec<&Explanation>>();
+        if records.is_empty() {
+            continue;
+        }
+        lines.push(format!("Part {}:", part));
+        let width = records
+            .iter()
+            .map(|e| e.subject.chars().count())
+            .max()
+            .unwrap_or(0);
+        for e in records {
+            lines.push(format!(
+                "  {:<width$}  {}",
+                e.subject,
+                e.detail,
+                width = width
+            ));
+        }
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_by_part() {
+        let explanations = vec![
+            Explanation::new(2, "line 1", "b"),
+            Explanation::new(1, "line 10", "a"),
+            Explanation::new(1, "line 2", "c"),
+        ];
+        assert_eq!(
+            render(&explanations),
+            "Part 1:\n  line 10  a\n  line 2   c\nPart 2:\n  line 1  b"
+        );
+    }
+}
@@ -0,0 +1,67 @@
+//! The example inputs in `fixtures/day<N>/`.
+//!
+//! A fixture is an input `<name>.txt` with its expected answers in
+//! `<name>.answers` (the `Part 1: ..` / `Part 2: ..` lines the runner prints;
+//! a missing part isn't checked) and optional parameters in `<name>.params`
+//! as `name=value` lines.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::batch::{parse_answers, read_params};
+use crate::runner::Params;
+
+pub struct Fixture {
+    pub name: String,
+    pub input: String,
+    pub params: Params,
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+}
+
+pub fn fixtures_dir(day: i16) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("fixtures")
+        .join(format!("day{}", day))
+}
+
+fn inputs(dir: &Path) -> Vec<PathBuf> {
+    let mut inputs = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+                .collect::<Vec<PathBuf>>()
+        })
+        .unwrap_or_default();
+    inputs.sort();
+    inputs
+}
+
+fn read_fixture(input_path: &Path) -> Result<Fixture, String> {
+    let name = input_path
+        .file_stem()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let input = fs::read_to_string(input_path).map_err(|e| format!("{}: {}", name, e))?;
+    let params = read_params(&input_path.with_extension("params"))
+        .map_err(|e| format!("{}: invalid params: {}", name, e))?;
+    let (part1, part2) = fs::read_to_string(input_path.with_extension("answers"))
+        .map(|answers| parse_answers(&answers))
+        .unwrap_or((None, None));
+    Ok(Fixture {
+        name,
+        input,
+        params,
+        part1,
+        part2,
+    })
+}
+
+/// Every fixture for a day, in name order
+pub fn read_fixtures(day: i16) -> Result<Vec<Fixture>, String> {
+    inputs(&fixtures_dir(day))
+        .iter()
+        .map(|path| read_fixture(path))
+        .collect()
+}
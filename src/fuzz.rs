@@ -0,0 +1,264 @@
+//! Mutation fuzzing of each day's parser and solvers, seeded from the
+//! examples in `fixtures/` so it runs offline.
+//!
+//! Malformed input should come back as an error; a panic or a solver which
+//! never finishes is a finding.
+
+use std::fmt;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use crate::fixtures::read_fixtures;
+use crate::runner::{catching, parse, solve, Params};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Target {
+    Parse,
+    Solve,
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Target::Parse => write!(f, "parse"),
+            Target::Solve => write!(f, "solve"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Finding {
+    pub day: i16,
+    pub target: Target,
+    pub message: String,
+    /// The smallest input found which still fails
+    pub input: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "day{} {}: {}\n  input: {:?}",
+            self.day, self.target, self.message, self.input
+        )
+    }
+}
+
+pub struct FuzzConfig {
+    pub iterations: usize,
+    pub seed: u64,
+    /// How long one run may take before it counts as a hang
+    pub timeout: Duration,
+}
+
+/// Tokens which tend to upset parsers
+const INTERESTING: [&str; 16] = [
+    "",
+    "\n",
+    "\n\n",
+    " ",
+    "0",
+    "-1",
+    "7",
+    "8",
+    "99999999999999999999",
+    "-9223372036854775808",
+    ",",
+    ":",
+    "|",
+    "#",
+    "é",
+    "mul(",
+];
+
+/// The most attempts spent shrinking one finding
+const MAX_SHRINK_ATTEMPTS: usize = 400;
+
+/// xorshift64*, so runs with the same seed mutate the same way
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Rng(seed.max(1))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        if n == 0 {
+            0
+        } else {
+            (self.next() % n as u64) as usize
+        }
+    }
+}
+
+fn mutate_once(rng: &mut Rng, chars: &mut Vec<char>, seeds: &[String]) {
+    let at = rng.below(chars.len() + 1);
+    let len = (1 + rng.below(8)).min(chars.len() - at.min(chars.len()));
+    match rng.below(7) {
+        0 => {
+            chars.drain(at..at + len);
+        }
+        1 => {
+            let token = INTERESTING[rng.below(INTERESTING.len())];
+            chars.splice(at..at, token.chars());
+        }
+        2 => {
+            let copy = chars[at..at + len].to_vec();
+            chars.splice(at..at, copy);
+        }
+        3 => {
+            if at < chars.len() {
+                let seed = &seeds[rng.below(seeds.len())];
+                chars[at] = seed.chars().nth(rng.below(seed.len())).unwrap_or(' ');
+            }
+        }
+        4 => chars.truncate(at),
+        5 => {
+            let seed = &seeds[rng.below(seeds.len())];
+            let lines = seed.lines().collect::<Vec<&str>>();
+            let line = lines.get(rng.below(lines.len())).copied().unwrap_or("");
+            chars.splice(at..at, line.chars().chain(std::iter::once('\n')));
+        }
+        _ => {
+            // swap a number for an interesting one
+            let start = (at..chars.len()).find(|i| chars[*i].is_ascii_digit());
+            if let Some(start) = start {
+                let end = (start..chars.len())
+                    .find(|i| !chars[*i].is_ascii_digit())
+                    .unwrap_or(chars.len());
+                let token = INTERESTING[4 + rng.below(6)];
+                chars.splice(start..end, token.chars());
+            }
+        }
+    }
+}
+
+fn mutate(rng: &mut Rng, seeds: &[String], seed: &str) -> String {
+    let mut chars = seed.chars().collect::<Vec<char>>();
+    for _ in 0..1 + rng.below(4) {
+        mutate_once(rng, &mut chars, seeds);
+    }
+    chars.into_iter().collect()
+}
+
+/// Runs one target on a separate thread, returning why it failed: a panic or
+/// running past the timeout. Errors for malformed input aren't failures.
+fn run(
+    day: i16,
+    target: Target,
+    input: &str,
+    params: &Params,
+    timeout: Duration,
+) -> Option<String> {
+    let (sender, receiver) = mpsc::channel();
+    let (input, params) = (input.to_owned(), params.clone());
+    // a hung solver can't be cancelled, so it's left to finish on its own
+    thread::spawn(move || {
+        let result = catching(|| match target {
+            Target::Parse => parse(day, input, &params).map(drop),
+            Target::Solve => solve(day, input, &params).map(drop),
+        });
+        let _ = sender.send(result.err());
+    });
+
+    match receiver.recv_timeout(timeout) {
+        Ok(panic) => panic.map(|message| format!("panicked: {}", message)),
+        Err(_) => Some(format!("took longer than {}s", timeout.as_secs_f64())),
+    }
+}
+
+/// Removes lines, then characters, from a panicking input for as long as it
+/// still panics. Hangs aren't shrunk, as every attempt would wait out the
+/// timeout.
+fn shrink(day: i16, target: Target, input: &str, params: &Params, timeout: Duration) -> String {
+    let panics = |candidate: &str| {
+        run(day, target, candidate, params, timeout).is_some_and(|m| m.starts_with("panicked"))
+    };
+    let mut attempts = 0;
+
+    let mut lines = input.split_inclusive('\n').collect::<Vec<&str>>();
+    let mut i = 0;
+    while i < lines.len() && attempts < MAX_SHRINK_ATTEMPTS {
+        attempts += 1;
+        let mut candidate = lines.clone();
+        candidate.remove(i);
+        if panics(&candidate.concat()) {
+            lines = candidate;
+        } else {
+            i += 1;
+        }
+    }
+
+    let mut chars = lines.concat().chars().collect::<Vec<char>>();
+    let mut i = 0;
+    while i < chars.len() && attempts < MAX_SHRINK_ATTEMPTS {
+        attempts += 1;
+        let mut candidate = chars.clone();
+        candidate.remove(i);
+        if panics(&candidate.iter().collect::<String>()) {
+            chars = candidate;
+        } else {
+            i += 1;
+        }
+    }
+
+    chars.into_iter().collect()
+}
+
+/// Fuzzes a day's parser and solvers with mutations of its fixtures,
+/// returning one finding per distinct failure.
+pub fn fuzz_day(day: i16, config: &FuzzConfig) -> Result<Vec<Finding>, String> {
+    let fixtures = read_fixtures(day)?;
+    if fixtures.is_empty() {
+        return Err(format!("Day {} has no fixtures to seed from", day));
+    }
+    let seeds = fixtures
+        .iter()
+        .map(|fixture| fixture.input.clone())
+        .collect::<Vec<String>>();
+    let targets = if parse(day, String::new(), &Params::default()).is_some() {
+        vec![Target::Parse, Target::Solve]
+    } else {
+        vec![Target::Solve]
+    };
+
+    let mut rng = Rng::new(config.seed);
+    let mut findings: Vec<Finding> = vec![];
+    for _ in 0..config.iterations {
+        let fixture = &fixtures[rng.below(fixtures.len())];
+        let input = mutate(&mut rng, &seeds, &fixture.input);
+        for target in &targets {
+            let Some(message) = run(day, *target, &input, &fixture.params, config.timeout) else {
+                continue;
+            };
+            if findings
+                .iter()
+                .any(|f| f.target == *target && f.message == message)
+            {
+                continue;
+            }
+            let input = if message.starts_with("panicked") {
+                shrink(day, *target, &input, &fixture.params, config.timeout)
+            } else {
+                input.clone()
+            };
+            findings.push(Finding {
+                day,
+                target: *target,
+                message,
+                input,
+            });
+        }
+    }
+
+    Ok(findings)
+}
@@ -1,270 +1,361 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use std::fs::File;
-use std::io::prelude::*;
-
-use day1::day1;
-use day10::day10;
-use day11::day11;
-use day12::day12;
-use day13::day13;
-use day14::{day14, Day14Input};
-use day15::day15;
-use day16::day16;
-use day17::day17;
-use day18::day18;
-use day19::day19;
-use day2::day2;
-use day20::day20;
-use day3::day3;
-use day4::day4;
-use day5::day5;
-use day6::day6;
-use day7::day7;
-use day8::day8;
-use day9::day9;
-
-mod day1;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
-mod day2;
-mod day20;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::process::exit;
+use std::time::Duration;
+
+use advent_of_code_2024::batch::{format_matrix, run_batch};
+use advent_of_code_2024::day1::{self, day1_external, ExternalSort};
+use advent_of_code_2024::day3::day3_stream;
+use advent_of_code_2024::day5;
+use advent_of_code_2024::explain::render;
+use advent_of_code_2024::fuzz::{fuzz_day, FuzzConfig};
+use advent_of_code_2024::player::play;
+use advent_of_code_2024::repl;
+use advent_of_code_2024::runner::{
+    self, animation, explain, quiet_panics, read_input, render_state, solve, Params,
+};
+use advent_of_code_2024::server::{serve, ServerConfig};
 
 #[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
+#[command(version, about, long_about = None, subcommand_negates_reqs = true)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[arg(long, required = true)]
+    day: Option<i16>,
+
+    /// Run the day on every file in this directory instead of its own input.
+    /// A sibling `<name>.answers` file, if present, is checked against.
+    #[arg(long)]
+    inputs_dir: Option<PathBuf>,
+
+    /// Print how the answers were derived, for days which support it
+    #[arg(long)]
+    explain: bool,
+
+    /// Draw the day's state: `text` or `ansi` to the terminal, or a path
+    /// ending in .txt, .ppm, .png or .svg
     #[arg(long)]
-    day: i16,
+    render: Option<String>,
+
+    /// Step through the day's simulation in the terminal
+    #[arg(long)]
+    animate: bool,
+
+    /// Write day 1's pairings and similarity contributions as CSV files in
+    /// this directory, and print statistics about the distances
+    #[arg(long)]
+    report: Option<PathBuf>,
+
+    /// Write day 5's page-ordering rules to this file as a Graphviz graph
+    #[arg(long)]
+    dot: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Serve solvers over HTTP, e.g. `POST /days/14/solve?width=11&height=7`
+    /// with the input as the body
+    Serve {
+        #[arg(long, default_value_t = 8024)]
+        port: u16,
+
+        /// Largest accepted input
+        #[arg(long, default_value_t = 1024 * 1024)]
+        max_body_bytes: usize,
+
+        /// How long a solver may run before the request fails
+        #[arg(long, default_value_t = 30)]
+        timeout_secs: u64,
+
+        /// Most solvers running at once; further requests get a 503
+        #[arg(long, default_value_t = 4)]
+        max_solves: usize,
+    },
+    /// Explore a day's input interactively
+    Repl {
+        #[arg(long, default_value_t = 1)]
+        day: i16,
+    },
+    /// Feed mutated examples to the parsers and solvers, reporting panics and
+    /// hangs
+    Fuzz {
+        /// Fuzz only this day rather than all of them
+        #[arg(long)]
+        day: Option<i16>,
+
+        /// Mutated inputs to try per day
+        #[arg(long, default_value_t = 1000)]
+        iterations: usize,
+
+        #[arg(long, default_value_t = 1)]
+        seed: u64,
+
+        /// How long one run may take before it counts as a hang
+        #[arg(long, default_value_t = 10)]
+        timeout_secs: u64,
+    },
+    /// Solve day 1 for location lists too large for memory, by sorting them
+    /// in runs on disk
+    ExternalSort {
+        /// The location lists, in the puzzle's format
+        path: PathBuf,
+
+        /// Values from each list sorted in memory at a time
+        #[arg(long, default_value_t = 1_000_000)]
+        run_length: usize,
+
+        /// Where the sorted runs are written, by default the system's
+        /// temporary directory
+        #[arg(long)]
+        temp_dir: Option<PathBuf>,
+    },
+    /// Solve day 3 for a memory dump too large to load, scanning it a chunk
+    /// at a time
+    ScanMemory {
+        /// The corrupted memory
+        path: PathBuf,
+
+        /// Bytes read at a time
+        #[arg(long, default_value_t = 64 * 1024)]
+        chunk_size: usize,
+    },
 }
+
 fn main() {
     let cli = Cli::parse();
 
-    match cli.day {
-        1 => {
-            let mut input = String::new();
-            let mut file = File::open("src/day1_input.txt").expect("Error opening input");
-            file.read_to_string(&mut input)
-                .expect("Error reading input");
-            let result = day1(input);
-            println!("Day 1 result:");
-            println!("Part 1: {}", result.diff_sum);
-            println!("Part 2: {}", result.similarity_score);
-        }
-        2 => {
-            let mut input = String::new();
-            let mut file = File::open("src/day2_input.txt").expect("Error opening input");
-            file.read_to_string(&mut input)
-                .expect("Error reading input");
-            let result = day2(input);
-            println!("Day 2 result:");
-            println!("Part 1: {}", result.num_safe);
-            println!("Part 2: {}", result.num_safe_with_dampener);
-        }
-        3 => {
-            let mut input = String::new();
-            let mut file = File::open("src/day3_input.txt").expect("Error opening input");
-            file.read_to_string(&mut input)
-                .expect("Error reading input");
-            let result = day3(input);
-            println!("Day 3 result:");
-            println!("Part 1: {}", result.part1);
-            println!("Part 2: {}", result.part2);
-        }
-        4 => {
-            let mut input = String::new();
-            let mut file = File::open("src/day4_input.txt").expect("Error opening input");
-            file.read_to_string(&mut input)
-                .expect("Error reading input");
-            let result = day4(input);
-            println!("Day 4 result:");
-            println!("Part 1: {}", result.part1);
-            println!("Part 2: {}", result.part2);
-        }
-        5 => {
-            let mut input = String::new();
-            let mut file = File::open("src/day5_input.txt").expect("Error opening input");
-            file.read_to_string(&mut input)
-                .expect("Error reading input");
-            let result = day5(input);
-            println!("Day 5 result:");
-            println!("Part 1: {}", result.part1);
-            println!("Part 2: {}", result.part2);
-        }
-        6 => {
-            let mut input = String::new();
-            let mut file = File::open("src/day6_input.txt").expect("Error opening input");
-            file.read_to_string(&mut input)
-                .expect("Error reading input");
-            let result = day6(input);
-            println!("Day 6 result:");
-            println!("Part 1: {}", result.part1);
-            println!("Part 2: {}", result.part2);
+    if let Some(Command::Serve {
+        port,
+        max_body_bytes,
+        timeout_secs,
+        max_solves,
+    }) = cli.command
+    {
+        quiet_panics();
+        let config = ServerConfig {
+            port,
+            max_body_bytes,
+            solve_timeout: Duration::from_secs(timeout_secs),
+            max_solves,
+        };
+        if let Err(e) = serve(config) {
+            eprintln!("Error starting server: {}", e);
+            exit(1);
         }
-        7 => {
-            let mut input = String::new();
-            let mut file = File::open("src/day7_input.txt").expect("Error opening input");
-            file.read_to_string(&mut input)
-                .expect("Error reading input");
-            let result = day7(input);
-            println!("Day 7 result:");
-            println!("Part 1: {}", result.part1);
-            println!("Part 2: {}", result.part2);
-        }
-        8 => {
-            let mut input = String::new();
-            let mut file = File::open("src/day8_input.txt").expect("Error opening input");
-            file.read_to_string(&mut input)
-                .expect("Error reading input");
-            let result = day8(input);
-            println!("Day 8 result:");
-            println!("Part 1: {}", result.part1);
-            println!("Part 2: {}", result.part2);
+        return;
+    }
+
+    if let Some(Command::Repl { day }) = cli.command {
+        quiet_panics();
+        repl::run(day);
+        return;
+    }
+
+    if let Some(Command::Fuzz {
+        day,
+        iterations,
+        seed,
+        timeout_secs,
+    }) = cli.command
+    {
+        quiet_panics();
+        let config = FuzzConfig {
+            iterations,
+            seed,
+            timeout: Duration::from_secs(timeout_secs),
+        };
+        let mut found = 0;
+        for day in day.map_or(runner::DAYS, |day| day..=day) {
+            match fuzz_day(day, &config) {
+                Ok(findings) => {
+                    println!("Day {}: {} findings", day, findings.len());
+                    findings.iter().for_each(|finding| println!("{}", finding));
+                    found += findings.len();
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    exit(1);
+                }
+            }
         }
-        9 => {
-            let mut input = String::new();
-            let mut file = File::open("src/day9_input.txt").expect("Error opening input");
-            file.read_to_string(&mut input)
-                .expect("Error reading input");
-            let result = day9(input);
-            println!("Day 9 result:");
-            println!("Part 1: {}", result.part1);
-            println!("Part 2: {}", result.part2);
+        if found > 0 {
+            exit(1);
         }
-        10 => {
-            let mut input = String::new();
-            let mut file = File::open("src/day10_input.txt").expect("Error opening input");
-            file.read_to_string(&mut input)
-                .expect("Error reading input");
-            let result = day10(input);
-            println!("Day 10 result:");
-            println!("Part 1: {}", result.part1);
-            println!("Part 2: {}", result.part2);
+        return;
+    }
+
+    if let Some(Command::ExternalSort {
+        path,
+        run_length,
+        temp_dir,
+    }) = cli.command
+    {
+        let config = ExternalSort {
+            run_length,
+            temp_dir: temp_dir.unwrap_or_else(std::env::temp_dir),
+        };
+        let result = File::open(&path)
+            .map_err(|e| format!("Error opening {}: {}", path.display(), e))
+            .and_then(|file| day1_external(BufReader::new(file), &config));
+        match result {
+            Ok(result) => {
+                println!("Day 1 result:");
+                println!("Part 1: {}", result.diff_sum);
+                println!("Part 2: {}", result.similarity_score);
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                exit(1);
+            }
         }
-        11 => {
-            let mut input = String::new();
-            let mut file = File::open("src/day11_input.txt").expect("Error opening input");
-            file.read_to_string(&mut input)
-                .expect("Error reading input");
-            let result = day11(input);
-            println!("Day 11 result:");
-            println!("Part 1: {}", result.part1);
-            println!("Part 2: {}", result.part2);
+        return;
+    }
+
+    if let Some(Command::ScanMemory { path, chunk_size }) = cli.command {
+        let result = File::open(&path)
+            .map_err(|e| format!("Error opening {}: {}", path.display(), e))
+            .and_then(|file| day3_stream(file, chunk_size));
+        match result {
+            Ok(result) => {
+                println!("Day 3 result:");
+                println!("Part 1: {}", result.part1);
+                println!("Part 2: {}", result.part2);
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                exit(1);
+            }
         }
-        12 => {
-            let mut input = String::new();
-            let mut file = File::open("src/day12_input.txt").expect("Error opening input");
-            file.read_to_string(&mut input)
-                .expect("Error reading input");
-            let result = day12(input);
-            println!("Day 12 result:");
-            println!("Part 1: {}", result.part1);
-            println!("Part 2: {}", result.part2);
+        return;
+    }
+
+    let day = cli.day.unwrap();
+
+    if let Some(dir) = cli.inputs_dir {
+        quiet_panics();
+        match run_batch(day, &dir) {
+            Ok(rows) => {
+                println!("Day {} results:", day);
+                println!("{}", format_matrix(&rows));
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                exit(1);
+            }
         }
-        13 => {
-            let mut input = String::new();
-            let mut file = File::open("src/day13_input.txt").expect("Error opening input");
-            file.read_to_string(&mut input)
-                .expect("Error reading input");
-            let result = day13(input);
-            println!("Day 13 result:");
-            println!("Part 1: {}", result.part1);
-            println!("Part 2: {}", result.part2);
+        return;
+    }
+
+    if !runner::DAYS.contains(&day) {
+        panic!("Unknown or unfinished day {}", day);
+    }
+
+    let input = read_input(day);
+
+    if let Some(path) = cli.dot {
+        if day != 5 {
+            eprintln!("Day {} has no graph to export", day);
+            exit(1);
         }
-        14 => {
-            let mut input = String::new();
-            let mut file = File::open("src/day14_input.txt").expect("Error opening input");
-            file.read_to_string(&mut input)
-                .expect("Error reading input");
-            let result = day14(Day14Input {
-                robots: &input,
-                width: 101,
-                height: 103,
-            });
-            println!("Day 14 result:");
-            println!("Part 1: {}", result.part1);
-            println!("Part 2: {}", result.part2);
+        let written = day5::to_dot(input).and_then(|dot| {
+            std::fs::write(&path, dot)
+                .map_err(|e| format!("Error writing {}: {}", path.display(), e))
+        });
+        if let Err(e) = written {
+            eprintln!("Error: {}", e);
+            exit(1);
         }
-        15 => {
-            let mut input = String::new();
-            let mut file = File::open("src/day15_input.txt").expect("Error opening input");
-            file.read_to_string(&mut input)
-                .expect("Error reading input");
-            let result = day15(input.to_owned());
-            println!("Day 15 result:");
-            println!("Part 1: {}", result.part1);
-            println!("Part 2: {}", result.part2);
+        println!("Wrote {}", path.display());
+        return;
+    }
+
+    if let Some(dir) = cli.report {
+        if day != 1 {
+            eprintln!("Day {} has no report", day);
+            exit(1);
         }
-        16 => {
-            let mut input = String::new();
-            let mut file = File::open("src/day16_input.txt").expect("Error opening input");
-            file.read_to_string(&mut input)
-                .expect("Error reading input");
-            let result = day16(input.to_owned());
-            println!("Day 16 result:");
-            println!("Part 1: {}", result.part1);
-            println!("Part 2: {}", result.part2);
+        if let Err(e) = write_report(input, &dir) {
+            eprintln!("Error: {}", e);
+            exit(1);
         }
-        17 => {
-            let mut input = String::new();
-            let mut file = File::open("src/day17_input.txt").expect("Error opening input");
-            file.read_to_string(&mut input)
-                .expect("Error reading input");
-            let result = day17(input.to_owned());
-            println!("Day 17 result:");
-            println!("Part 1: {}", result.part1);
-            println!("Part 2: {}", result.part2);
+        return;
+    }
+
+    if cli.animate {
+        match animation(day, &input, &Params::default()) {
+            Some(Ok(animation)) => play(animation.as_ref()),
+            Some(Err(e)) => {
+                eprintln!("Error: {}", e);
+                exit(1);
+            }
+            None => {
+                eprintln!("Day {} has no simulation to animate", day);
+                exit(1);
+            }
         }
-        18 => {
-            let mut input = String::new();
-            let mut file = File::open("src/day18_input.txt").expect("Error opening input");
-            file.read_to_string(&mut input)
-                .expect("Error reading input");
-            let result = day18(day18::InputDay18 {
-                bytes: input,
-                grid_size: 71,
-                falls: 1024,
-            });
-            println!("Day 18 result:");
-            println!("Part 1: {}", result.part1);
-            println!("Part 2: {}", result.part2);
+        return;
+    }
+
+    let result = match solve(day, input.clone(), &Params::default()).unwrap() {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            exit(1);
         }
-        19 => {
-            let mut input = String::new();
-            let mut file = File::open("src/day19_input.txt").expect("Error opening input");
-            file.read_to_string(&mut input)
-                .expect("Error reading input");
-            let result = day19(input);
-            println!("Day 19 result:");
-            println!("Part 1: {}", result.part1);
-            println!("Part 2: {}", result.part2);
+    };
+    println!("Day {} result:", day);
+    println!("Part 1: {}", result.part1);
+    println!("Part 2: {}", result.part2);
+
+    if cli.explain {
+        match explain(day, input, &Params::default()) {
+            Some(Ok(explanations)) => println!("{}", render(&explanations)),
+            Some(Err(e)) => println!("Error: {}", e),
+            None => println!("Day {} cannot explain its answers", day),
         }
-        20 => {
-            let mut input = String::new();
-            let mut file = File::open("src/day20_input.txt").expect("Error opening input");
-            file.read_to_string(&mut input)
-                .expect("Error reading input");
-            let result = day20(day20::Day20Input {
-                maze: input,
-                cheat_threshold: 100,
-            });
-            println!("Day 20 result:");
-            println!("Part 1: {}", result.part1);
-            println!("Part 2: {}", result.part2);
+    }
+
+    if let Some(target) = cli.render {
+        let state = match render_state(day, read_input(day), &Params::default()) {
+            Some(Ok(state)) => state,
+            Some(Err(e)) => {
+                eprintln!("Error: {}", e);
+                exit(1);
+            }
+            None => {
+                eprintln!("Day {} has no state to render", day);
+                exit(1);
+            }
+        };
+        match target.as_str() {
+            "text" => println!("{}", state.to_text()),
+            "ansi" => println!("{}", state.to_ansi()),
+            path => {
+                if let Err(e) = state.save(Path::new(path)) {
+                    eprintln!("Error writing {}: {}", path, e);
+                    exit(1);
+                }
+            }
         }
-        _ => panic!("Unknown or unfinished day {}", cli.day),
     }
 }
+
+fn write_report(input: String, dir: &Path) -> Result<(), String> {
+    let report = day1::report(input)?;
+    std::fs::create_dir_all(dir).map_err(|e| format!("Error creating {}: {}", dir.display(), e))?;
+    for (name, csv) in [
+        ("pairings.csv", report.pairings_csv()),
+        ("contributions.csv", report.contributions_csv()),
+    ] {
+        let path = dir.join(name);
+        std::fs::write(&path, csv)
+            .map_err(|e| format!("Error writing {}: {}", path.display(), e))?;
+        println!("Wrote {}", path.display());
+    }
+    println!("{}", report.stats);
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Parses a number, naming the text which isn't one
+pub fn number<T>(text: &str) -> Result<T, String>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    text.parse::<T>()
+        .map_err(|e| format!("Invalid number {:?}: {}", text, e))
+}
+
+/// Splits `text` around the first `separator`
+pub fn split<'a>(text: &'a str, separator: &str) -> Result<(&'a str, &'a str), String> {
+    text.split_once(separator)
+        .ok_or_else(|| format!("Expected {:?} in {:?}", separator, text))
+}
+
+/// Prefixes an error with the (0-based) `index` of the line it came from
+pub fn on_line<T>(index: usize, result: Result<T, String>) -> Result<T, String> {
+    result.map_err(|e| format!("Line {}: {}", index + 1, e))
+}
+
+/// Parses every line of `text`, reporting the first which fails
+pub fn lines<'a, T>(
+    text: &'a str,
+    mut parse: impl FnMut(&'a str) -> Result<T, String>,
+) -> Result<Vec<T>, String> {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| on_line(i, parse(line)))
+        .collect()
+}
+
+/// A rectangular, non-empty grid of characters, one row per line
+pub fn grid(text: &str) -> Result<Vec<Vec<char>>, String> {
+    let rows = text
+        .lines()
+        .map(|line| line.chars().collect::<Vec<char>>())
+        .collect::<Vec<Vec<char>>>();
+    let width = rows.first().map_or(0, |row| row.len());
+    if width == 0 {
+        return Err("Empty grid".to_owned());
+    }
+    match rows.iter().position(|row| row.len() != width) {
+        Some(i) => on_line(
+            i,
+            Err(format!("Expected {} columns, got {}", width, rows[i].len())),
+        ),
+        None => Ok(rows),
+    }
+}
+
+/// The (x, y) position of the only `ch` in a grid
+pub fn find_one(grid: &[Vec<char>], ch: char) -> Result<(usize, usize), String> {
+    let mut found = grid.iter().enumerate().flat_map(|(y, row)| {
+        row.iter()
+            .enumerate()
+            .filter(move |(_, c)| **c == ch)
+            .map(move |(x, _)| (x, y))
+    });
+    match (found.next(), found.next()) {
+        (Some(position), None) => Ok(position),
+        (None, _) => Err(format!("No {} in grid", ch)),
+        (Some(_), Some(_)) => Err(format!("More than one {} in grid", ch)),
+    }
+}
+
+/// Checks that every cell is one of `allowed`
+pub fn only_chars(grid: &[Vec<char>], allowed: &str) -> Result<(), String> {
+    for (y, row) in grid.iter().enumerate() {
+        if let Some(ch) = row.iter().find(|ch| !allowed.contains(**ch)) {
+            return on_line(y, Err(format!("Unexpected character {:?}", ch)));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_bad_lines() {
+        assert_eq!(
+            lines("1\n2\nx", number::<u8>),
+            Err("Line 3: Invalid number \"x\": invalid digit found in string".to_owned())
+        );
+        assert_eq!(split("a|b", "|"), Ok(("a", "b")));
+        assert!(split("ab", "|").is_err());
+    }
+
+    #[test]
+    fn parses_grids() {
+        let cells = grid("#S\n.E").unwrap();
+        assert_eq!(find_one(&cells, 'E'), Ok((1, 1)));
+        assert_eq!(find_one(&cells, 'x'), Err("No x in grid".to_owned()));
+        assert_eq!(only_chars(&cells, "#.SE"), Ok(()));
+        assert!(only_chars(&cells, "#.").is_err());
+        assert_eq!(
+            grid("##\n#"),
+            Err("Line 2: Expected 2 columns, got 1".to_owned())
+        );
+        assert_eq!(grid(""), Err("Empty grid".to_owned()));
+    }
+}
@@ -0,0 +1,233 @@
+use std::io::{self, BufRead, Write};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use crate::render::{Render, CLEAR_SCREEN};
+
+const MIN_FPS: u32 = 1;
+const MAX_FPS: u32 = 240;
+const DEFAULT_FPS: u32 = 10;
+
+const HELP: &str = "[enter] play/pause  n next  b back  + faster  - slower  g <step> jump  q quit";
+
+/// A simulation which can draw any of its steps, so the player can move
+/// backwards as cheaply as forwards.
+pub trait Animation {
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn frame(&self, step: usize) -> Box<dyn Render + '_>;
+}
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    TogglePause,
+    Forward,
+    Back,
+    Faster,
+    Slower,
+    Jump(usize),
+    Quit,
+}
+
+fn parse_command(line: &str) -> Option<Command> {
+    let line = line.trim();
+    match line {
+        "" | "p" => Some(Command::TogglePause),
+        "n" => Some(Command::Forward),
+        "b" => Some(Command::Back),
+        "+" => Some(Command::Faster),
+        "-" => Some(Command::Slower),
+        "q" => Some(Command::Quit),
+        _ => line
+            .strip_prefix('g')
+            .and_then(|step| str::parse::<usize>(step.trim()).ok())
+            .map(Command::Jump),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Player {
+    len: usize,
+    step: usize,
+    playing: bool,
+    fps: u32,
+}
+
+impl Player {
+    fn new(len: usize) -> Self {
+        Player {
+            len,
+            step: 0,
+            playing: false,
+            fps: DEFAULT_FPS,
+        }
+    }
+
+    fn last_step(&self) -> usize {
+        self.len.saturating_sub(1)
+    }
+
+    /// Returns false once the player should stop
+    fn apply(&mut self, command: Command) -> bool {
+        match command {
+            Command::TogglePause => self.playing = !self.playing,
+            Command::Forward => {
+                self.playing = false;
+                self.step = (self.step + 1).min(self.last_step());
+            }
+            Command::Back => {
+                self.playing = false;
+                self.step = self.step.saturating_sub(1);
+            }
+            Command::Faster => self.fps = (self.fps * 2).min(MAX_FPS),
+            Command::Slower => self.fps = (self.fps / 2).max(MIN_FPS),
+            Command::Jump(step) => self.step = step.min(self.last_step()),
+            Command::Quit => return false,
+        }
+        true
+    }
+
+    /// Advances one frame while playing, pausing at the end
+    fn tick(&mut self) {
+        if self.step < self.last_step() {
+            self.step += 1;
+        } else {
+            self.playing = false;
+        }
+    }
+
+    fn status(&self) -> String {
+        format!(
+            "step {}/{}  {}  {} fps",
+            self.step,
+            self.last_step(),
+            if self.playing { "playing" } else { "paused" },
+            self.fps
+        )
+    }
+}
+
+fn draw(animation: &dyn Animation, player: &Player) {
+    let mut stdout = io::stdout().lock();
+    let _ = write!(
+        stdout,
+        "{}{}\n{}\n{}\n",
+        CLEAR_SCREEN,
+        animation.frame(player.step).to_ansi(),
+        player.status(),
+        HELP
+    );
+    let _ = stdout.flush();
+}
+
+/// Plays an animation in the terminal. Commands are typed as lines, read on
+/// a separate thread so frames keep advancing while waiting for input.
+pub fn play(animation: &dyn Animation) {
+    if animation.is_empty() {
+        println!("Nothing to play");
+        return;
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            let Ok(line) = line else { break };
+            if let Some(command) = parse_command(&line) {
+                if sender.send(command).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+
+    let mut player = Player::new(animation.len());
+    draw(animation, &player);
+
+    loop {
+        let command = if player.playing {
+            match receiver.recv_timeout(Duration::from_secs(1) / player.fps) {
+                Ok(command) => Some(command),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        } else {
+            match receiver.recv() {
+                Ok(command) => Some(command),
+                Err(_) => break,
+            }
+        };
+
+        match command {
+            Some(command) => {
+                if !player.apply(command) {
+                    break;
+                }
+            }
+            None => player.tick(),
+        }
+        draw(animation, &player);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(parse_command(""), Some(Command::TogglePause));
+        assert_eq!(parse_command("n"), Some(Command::Forward));
+        assert_eq!(parse_command("g 42"), Some(Command::Jump(42)));
+        assert_eq!(parse_command("g42"), Some(Command::Jump(42)));
+        assert_eq!(parse_command("g x"), None);
+        assert_eq!(parse_command("what"), None);
+    }
+
+    #[test]
+    fn steps_within_bounds() {
+        let mut player = Player::new(3);
+        player.apply(Command::Back);
+        assert_eq!(player.step, 0);
+        player.apply(Command::Jump(10));
+        assert_eq!(player.step, 2);
+        player.apply(Command::Forward);
+        assert_eq!(player.step, 2);
+
+        player.apply(Command::Jump(1));
+        player.apply(Command::TogglePause);
+        player.tick();
+        player.tick();
+        assert_eq!(player.step, 2);
+        assert!(!player.playing);
+
+        player.apply(Command::Slower);
+        player.apply(Command::Slower);
+        player.apply(Command::Slower);
+        player.apply(Command::Slower);
+        assert_eq!(player.fps, MIN_FPS);
+        assert!(!player.apply(Command::Quit));
+    }
+
+    struct Empty;
+
+    impl Animation for Empty {
+        fn len(&self) -> usize {
+            0
+        }
+
+        fn frame(&self, _: usize) -> Box<dyn Render + '_> {
+            unreachable!("an empty animation has no frames")
+        }
+    }
+
+    #[test]
+    fn plays_nothing_when_empty() {
+        assert!(Empty.is_empty());
+        play(&Empty);
+    }
+}
@@ -0,0 +1,261 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+pub const CLEAR_SCREEN: &str = "\x1B[2J\x1B[1;1H";
+
+const IMAGE_SCALE: usize = 4;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Color(pub u8, pub u8, pub u8);
+
+pub const BLACK: Color = Color(0x10, 0x10, 0x10);
+pub const GREY: Color = Color(0x60, 0x60, 0x60);
+pub const WHITE: Color = Color(0xe0, 0xe0, 0xe0);
+pub const RED: Color = Color(0xe0, 0x30, 0x30);
+pub const GREEN: Color = Color(0x30, 0xc0, 0x40);
+pub const YELLOW: Color = Color(0xe0, 0xc0, 0x30);
+pub const BLUE: Color = Color(0x40, 0x70, 0xe0);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Tile {
+    pub ch: char,
+    pub color: Color,
+}
+
+impl Tile {
+    pub fn new(ch: char, color: Color) -> Self {
+        Tile { ch, color }
+    }
+}
+
+/// A grid-shaped state which can be drawn. Implementors only describe each
+/// cell; the output formats are shared.
+pub trait Render {
+    /// (width, height) in cells
+    fn size(&self) -> (usize, usize);
+
+    fn tile(&self, x: usize, y: usize) -> Tile;
+
+    fn to_text(&self) -> String {
+        let (width, height) = self.size();
+        (0..height)
+            .map(|y| (0..width).map(|x| self.tile(x, y).ch).collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    fn to_ansi(&self) -> String {
+        let (width, height) = self.size();
+        (0..height)
+            .map(|y| {
+                // only switch colour when it changes, to keep frames small
+                let (row, _) = (0..width).fold((String::new(), None), |(mut row, prev), x| {
+                    let Tile { ch, color } = self.tile(x, y);
+                    if prev != Some(color) {
+                        let Color(r, g, b) = color;
+                        row.push_str(&format!("\x1B[38;2;{};{};{}m", r, g, b));
+                    }
+                    row.push(ch);
+                    (row, Some(color))
+                });
+                format!("{}\x1B[0m", row)
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    /// Row-major RGB pixels, each cell drawn as a `scale`-sized square
+    fn to_pixels(&self, scale: usize) -> Vec<u8> {
+        let (width, height) = self.size();
+        let mut pixels = Vec::with_capacity(width * height * scale * scale * 3);
+        for y in 0..height * scale {
+            for x in 0..width * scale {
+                let Color(r, g, b) = self.tile(x / scale, y / scale).color;
+                pixels.extend_from_slice(&[r, g, b]);
+            }
+        }
+        pixels
+    }
+
+    fn to_ppm(&self, scale: usize) -> Vec<u8> {
+        let (width, height) = self.size();
+        let mut ppm = format!("P6\n{} {}\n255\n", width * scale, height * scale).into_bytes();
+        ppm.append(&mut self.to_pixels(scale));
+        ppm
+    }
+
+    fn to_png(&self, scale: usize) -> io::Result<Vec<u8>> {
+        let (width, height) = self.size();
+        encode_png(width * scale, height * scale, &self.to_pixels(scale))
+    }
+
+    fn to_svg(&self, scale: usize) -> String {
+        let (width, height) = self.size();
+        let rects = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let Color(r, g, b) = self.tile(x, y).color;
+                format!(
+                    r##"<rect x="{}" y="{}" width="{}" height="{}" fill="#{:02x}{:02x}{:02x}"/>"##,
+                    x * scale,
+                    y * scale,
+                    scale,
+                    scale,
+                    r,
+                    g,
+                    b
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">\n{}\n</svg>\n",
+            width * scale,
+            height * scale,
+            rects
+        )
+    }
+
+    /// Writes an image, choosing the format from the file extension
+    fn save(&self, path: &Path) -> io::Result<()> {
+        let contents = match path.extension().and_then(|e| e.to_str()) {
+            Some("ppm") => self.to_ppm(IMAGE_SCALE),
+            Some("png") => self.to_png(IMAGE_SCALE)?,
+            Some("svg") => self.to_svg(IMAGE_SCALE).into_bytes(),
+            Some("txt") => self.to_text().into_bytes(),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Unknown image format {}", path.display()),
+                ))
+            }
+        };
+        fs::write(path, contents)
+    }
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0xffffffff_u32, |crc, byte| {
+        (0..8).fold(crc ^ (*byte as u32), |c, _| {
+            if c & 1 == 1 {
+                0xedb88320 ^ (c >> 1)
+            } else {
+                c >> 1
+            }
+        })
+    }) ^ 0xffffffff
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (a, b) = bytes.iter().fold((1_u32, 0_u32), |(a, b), byte| {
+        let a = (a + *byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    (b << 16) | a
+}
+
+/// zlib stream using uncompressed deflate blocks, which every PNG reader
+/// accepts and needs no compression library.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let blocks = data.chunks(0xffff).collect::<Vec<&[u8]>>();
+    if blocks.is_empty() {
+        out.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    for (i, block) in blocks.iter().enumerate() {
+        let len = block.len() as u16;
+        out.push(if i == blocks.len() - 1 { 1 } else { 0 });
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn png_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// PNG has no empty images, so a state with no rows or columns can't be saved
+fn encode_png(width: usize, height: usize, rgb: &[u8]) -> io::Result<Vec<u8>> {
+    if width == 0 || height == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Can't write an empty image as PNG",
+        ));
+    }
+
+    let mut png = vec![0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+
+    let mut header = vec![];
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    // 8-bit RGB, default compression/filter, no interlace
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+    png_chunk(&mut png, b"IHDR", &header);
+
+    let scanlines = rgb
+        .chunks(width * 3)
+        .flat_map(|row| std::iter::once(0).chain(row.iter().copied()))
+        .collect::<Vec<u8>>();
+    png_chunk(&mut png, b"IDAT", &zlib_stored(&scanlines));
+    png_chunk(&mut png, b"IEND", &[]);
+
+    Ok(png)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Checkers;
+
+    impl Render for Checkers {
+        fn size(&self) -> (usize, usize) {
+            (3, 2)
+        }
+
+        fn tile(&self, x: usize, y: usize) -> Tile {
+            if (x + y).is_multiple_of(2) {
+                Tile::new('#', WHITE)
+            } else {
+                Tile::new('.', BLACK)
+            }
+        }
+    }
+
+    #[test]
+    fn renders_text_and_ansi() {
+        assert_eq!(Checkers.to_text(), "#.#\n.#.");
+        assert!(Checkers
+            .to_ansi()
+            .starts_with("\x1B[38;2;224;224;224m#\x1B[38;2;16;16;16m."));
+    }
+
+    #[test]
+    fn renders_images() {
+        let ppm = Checkers.to_ppm(2);
+        assert!(ppm.starts_with(b"P6\n6 4\n255\n"));
+        assert_eq!(ppm.len(), 11 + 6 * 4 * 3);
+
+        let png = Checkers.to_png(1).unwrap();
+        assert_eq!(&png[1..4], b"PNG");
+        assert_eq!(&png[png.len() - 8..png.len() - 4], b"IEND");
+
+        assert_eq!(Checkers.to_svg(1).matches("<rect").count(), 6);
+        assert!(Checkers.to_png(0).is_err());
+    }
+
+    #[test]
+    fn computes_checksums() {
+        assert_eq!(crc32(b"123456789"), 0xcbf43926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e60398);
+    }
+}
@@ -0,0 +1,374 @@
+use std::fs;
+use std::io::{self, BufRead, Write};
+
+use crate::day17::Debugger;
+use crate::player::Animation;
+use crate::runner::{animation, catching, describe, input_path, render_state, solve_part, Params};
+
+const HELP: &str = r"Commands:
+  day <n>              switch to day n and load its input
+  load <path>          load another input for the current day
+  parse                show the parsed input
+  part1 | part2        solve with the current input and parameters
+  set <name> <value>   set a parameter, e.g. width, falls or sequence_length
+  show params|state|input
+  step [n]             advance the simulation (or the day 17 program) n steps
+  reset                go back to the first step
+  history              list previous commands; !<n> runs one again
+  quit";
+
+pub struct Repl {
+    day: i16,
+    input: String,
+    params: Params,
+    /// Each part's answer, solved separately so one part failing or running
+    /// slowly doesn't hold up the other
+    answers: [Option<Result<String, String>>; 2],
+    /// The day's simulation, if it has one, built once for all the steps
+    animation: Option<Option<Result<Box<dyn Animation>, String>>>,
+    step: usize,
+    debugger: Option<Debugger>,
+    history: Vec<String>,
+}
+
+impl Repl {
+    pub fn new(day: i16, input: String) -> Self {
+        Repl {
+            day,
+            input,
+            params: Params::default(),
+            answers: [None, None],
+            animation: None,
+            step: 0,
+            debugger: None,
+            history: vec![],
+        }
+    }
+
+    fn prompt(&self) -> String {
+        format!("day{}> ", self.day)
+    }
+
+    /// Forgets anything derived from the input or parameters
+    fn invalidate(&mut self) {
+        self.answers = [None, None];
+        self.animation = None;
+        self.step = 0;
+        self.debugger = None;
+    }
+
+    fn load(&mut self, day: i16, path: &str) -> String {
+        match fs::read_to_string(path) {
+            Ok(input) => {
+                self.day = day;
+                self.input = input;
+                self.invalidate();
+                format!("Loaded {} ({} lines)", path, self.input.lines().count())
+            }
+            Err(e) => format!("Error reading {}: {}", path, e),
+        }
+    }
+
+    fn answer(&mut self, part: u8) -> String {
+        let (day, input, params) = (self.day, self.input.clone(), &self.params);
+        let answer = self.answers[part as usize - 1].get_or_insert_with(|| {
+            catching(|| solve_part(day, part, input, params)).and_then(|answer| {
+                answer.unwrap_or(Err(format!("Unknown or unfinished day {}", day)))
+            })
+        });
+        match answer {
+            Ok(answer) => answer.clone(),
+            Err(e) => format!("Error: {}", e),
+        }
+    }
+
+    /// The day's simulation, built on first use
+    fn animation(&mut self) -> Option<Result<&dyn Animation, String>> {
+        let (day, input, params) = (self.day, &self.input, &self.params);
+        let animation = self.animation.get_or_insert_with(|| {
+            catching(|| animation(day, input, params)).unwrap_or_else(|e| Some(Err(e)))
+        });
+        animation
+            .as_ref()
+            .map(|a| a.as_ref().map(|a| a.as_ref()).map_err(String::clone))
+    }
+
+    fn debugger(&mut self) -> Result<&mut Debugger, String> {
+        if self.debugger.is_none() {
+            let input = self.input.clone();
+            self.debugger = Some(catching(|| Debugger::new(input))??);
+        }
+        Ok(self.debugger.as_mut().unwrap())
+    }
+
+    fn show_state(&mut self) -> String {
+        if self.day == 17 {
+            return match self.debugger() {
+                Ok(debugger) => debugger.to_string(),
+                Err(e) => format!("Error: {}", e),
+            };
+        }
+        let (day, step) = (self.day, self.step);
+        let frame = self.animation().map(|a| {
+            a.and_then(|a| {
+                catching(|| (!a.is_empty()).then(|| a.frame(step.min(a.len() - 1)).to_text()))
+            })
+        });
+        let (input, params) = (&self.input, &self.params);
+        match frame {
+            Some(Ok(Some(text))) => format!("{}\nstep {}", text, step),
+            Some(Ok(None)) => format!("Day {} has no steps to show", day),
+            None => match catching(|| render_state(day, input.clone(), params)) {
+                Ok(Some(Ok(state))) => state.to_text(),
+                Ok(None) => format!("Day {} has no state to show", day),
+                Ok(Some(Err(e))) | Err(e) => format!("Error: {}", e),
+            },
+            Some(Err(e)) => format!("Error: {}", e),
+        }
+    }
+
+    fn step(&mut self, n: usize) -> String {
+        if self.day == 17 {
+            return match self.debugger() {
+                Ok(debugger) => {
+                    for _ in 0..n {
+                        if !debugger.step() {
+                            break;
+                        }
+                    }
+                    debugger.to_string()
+                }
+                Err(e) => format!("Error: {}", e),
+            };
+        }
+        let day = self.day;
+        match self
+            .animation()
+            .map(|a| a.map(|a| (!a.is_empty()).then(|| a.len())))
+        {
+            Some(Ok(None)) => format!("Day {} has no steps to show", day),
+            Some(Ok(Some(len))) => {
+                self.step = (self.step + n).min(len - 1);
+                self.show_state()
+            }
+            None => format!("Day {} has nothing to step through", day),
+            Some(Err(e)) => format!("Error: {}", e),
+        }
+    }
+
+    fn show_params(&self) -> String {
+        let Params {
+            width,
+            height,
+            grid_size,
+            falls,
+            cheat_threshold,
+            robots,
+            iterations,
+            sequence_length,
+            dampener,
+            min_step,
+            max_step,
+            monotonic,
+        } = &self.params;
+        let show = |value: Option<String>| value.unwrap_or("default".to_owned());
+        format!(
+            "width={} height={} grid_size={} falls={} cheat_threshold={} robots={} iterations={} sequence_length={} dampener={} min_step={} max_step={} monotonic={}",
+            show(width.map(|v| v.to_string())),
+            show(height.map(|v| v.to_string())),
+            show(grid_size.map(|v| v.to_string())),
+            show(falls.map(|v| v.to_string())),
+            show(cheat_threshold.map(|v| v.to_string())),
+            show(robots.map(|v| v.to_string())),
+            show(iterations.map(|v| v.to_string())),
+            show(sequence_length.map(|v| v.to_string())),
+            show(dampener.map(|v| v.to_string())),
+            show(min_step.map(|v| v.to_string())),
+            show(max_step.map(|v| v.to_string())),
+            show(monotonic.map(|v| v.to_string()))
+        )
+    }
+
+    /// Runs one command, returning its output, or None to quit
+    pub fn execute(&mut self, line: &str) -> Option<String> {
+        let line = line.trim();
+        if let Some(n) = line.strip_prefix('!') {
+            return match str::parse::<usize>(n)
+                .ok()
+                .and_then(|n| self.history.get(n.wrapping_sub(1)).cloned())
+            {
+                Some(command) => self.execute(&command),
+                None => Some(format!("No command {} in history", n)),
+            };
+        }
+        if !line.is_empty() {
+            self.history.push(line.to_owned());
+        }
+
+        let words = line.split_whitespace().collect::<Vec<&str>>();
+        let output = match words.as_slice() {
+            [] => "".to_owned(),
+            ["quit"] | ["exit"] => return None,
+            ["help"] => HELP.to_owned(),
+            ["day", n] => match str::parse::<i16>(n) {
+                Ok(day) => self.load(day, &input_path(day)),
+                Err(_) => format!("Invalid day {}", n),
+            },
+            ["load", path] => self.load(self.day, path),
+            ["parse"] => {
+                let (day, input) = (self.day, self.input.clone());
+                match catching(|| describe(day, input)) {
+                    Ok(Some(Ok(description))) => description,
+                    Ok(None) => format!("Day {} has no parsed view", day),
+                    Ok(Some(Err(e))) | Err(e) => format!("Error: {}", e),
+                }
+            }
+            ["part1"] => self.answer(1),
+            ["part2"] => self.answer(2),
+            ["set", name, value] => match self.params.set(name, value) {
+                Ok(()) => {
+                    self.invalidate();
+                    self.show_params()
+                }
+                Err(e) => e,
+            },
+            ["show", "params"] => self.show_params(),
+            ["show", "state"] => self.show_state(),
+            ["show", "input"] => self.input.clone(),
+            ["step"] => self.step(1),
+            ["step", n] => match str::parse::<usize>(n) {
+                Ok(n) => self.step(n),
+                Err(_) => format!("Invalid step count {}", n),
+            },
+            ["reset"] => {
+                self.step = 0;
+                self.debugger = None;
+                "Back to step 0".to_owned()
+            }
+            ["history"] => self
+                .history
+                .iter()
+                .enumerate()
+                .map(|(i, command)| format!("{:>4}  {}", i + 1, command))
+                .collect::<Vec<String>>()
+                .join("\n"),
+            _ => format!("Unknown command {}; try help", line),
+        };
+
+        Some(output)
+    }
+}
+
+pub fn run(day: i16) {
+    let mut repl = Repl::new(day, String::new());
+    println!("{}", repl.load(day, &input_path(day)));
+    println!("Type help for commands");
+
+    let stdin = io::stdin();
+    loop {
+        print!("{}", repl.prompt());
+        let _ = io::stdout().flush();
+
+        let mut line = String::new();
+        match stdin.lock().read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => match repl.execute(&line) {
+                Some(output) if output.is_empty() => {}
+                Some(output) => println!("{}", output),
+                None => break,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solves_with_params() {
+        let mut repl = Repl::new(
+            18,
+            "5,4\n4,2\n4,5\n3,0\n2,1\n6,3\n2,4\n1,5\n0,6\n3,3\n2,6\n5,1\n1,2\n5,5\n2,5\n6,5\n1,4\n0,4\n6,4\n1,1\n6,1\n1,0\n0,5\n1,6\n2,0".to_owned(),
+        );
+        repl.execute("set grid_size 7");
+        assert_eq!(
+            repl.execute("set falls 12"),
+            Some(
+                "width=default height=default grid_size=7 falls=12 cheat_threshold=default robots=default iterations=default sequence_length=default dampener=default min_step=default max_step=default monotonic=default"
+                    .to_owned()
+            )
+        );
+        assert_eq!(repl.execute("part1"), Some("22".to_owned()));
+        assert_eq!(repl.execute("part2"), Some("6,1".to_owned()));
+        assert_eq!(
+            repl.execute("set speed 1"),
+            Some("Unknown parameter speed".to_owned())
+        );
+        assert_eq!(
+            repl.execute("step 3"),
+            Some(
+                ".......\n.......\n....#..\n.......\n.....#.\n....#..\n.......\nstep 3".to_owned()
+            )
+        );
+    }
+
+    #[test]
+    fn solves_parts_separately() {
+        let mut repl = Repl::new(
+            14,
+            "p=0,4 v=3,-3\np=6,3 v=-1,-3\np=10,3 v=-1,2\np=2,0 v=2,-1\np=0,0 v=1,3\np=3,0 v=-2,-2\np=7,6 v=-1,-3\np=3,0 v=-1,-2\np=9,3 v=2,3\np=7,3 v=-1,2\np=2,4 v=2,-3\np=9,5 v=-3,-3".to_owned(),
+        );
+        repl.execute("set width 11");
+        repl.execute("set height 7");
+
+        // the example has no christmas tree, which only part 2 looks for
+        assert_eq!(repl.execute("part1"), Some("12".to_owned()));
+        assert!(repl.answers[1].is_none());
+    }
+
+    #[test]
+    fn builds_animation_once() {
+        let mut repl = Repl::new(18, "5,4\n4,2\n4,5".to_owned());
+        repl.execute("set grid_size 7");
+        assert!(repl.animation.is_none());
+
+        repl.execute("step");
+        assert!(repl.animation.is_some());
+        assert!(repl.execute("step 10").unwrap().ends_with("step 3"));
+
+        repl.execute("set falls 1");
+        assert!(repl.animation.is_none());
+    }
+
+    #[test]
+    fn steps_day17_program() {
+        let mut repl = Repl::new(
+            17,
+            "Register A: 729\nRegister B: 0\nRegister C: 0\n\nProgram: 0,1,5,4,3,0".to_owned(),
+        );
+        assert_eq!(
+            repl.execute("step 2"),
+            Some("A=364 B=0 C=0 ip=4 next: jnz if A != 0 goto 0\noutput: 4".to_owned())
+        );
+        assert_eq!(repl.execute("reset"), Some("Back to step 0".to_owned()));
+        assert!(repl.execute("parse").unwrap().starts_with("A=729 B=0 C=0"));
+    }
+
+    #[test]
+    fn keeps_history() {
+        let mut repl = Repl::new(1, "3   4\n4   3\n".to_owned());
+        repl.execute("part1");
+        repl.execute("bogus");
+        assert_eq!(
+            repl.execute("history"),
+            Some("   1  part1\n   2  bogus\n   3  history".to_owned())
+        );
+        assert_eq!(repl.execute("!1"), Some("0".to_owned()));
+        assert_eq!(
+            repl.execute("!9"),
+            Some("No command 9 in history".to_owned())
+        );
+        assert_eq!(repl.execute("quit"), None);
+    }
+}
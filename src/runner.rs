@@ -0,0 +1,511 @@
+use std::any::Any;
+use std::fs::File;
+use std::io::prelude::*;
+use std::panic;
+
+use crate::day1::day1;
+use crate::day10::day10;
+use crate::day11::day11;
+use crate::day12::day12;
+use crate::day13::day13;
+use crate::day14::{day14, Day14Input};
+use crate::day15::day15;
+use crate::day16::day16;
+use crate::day17::day17;
+use crate::day18::{day18, InputDay18};
+use crate::day19::day19;
+use crate::day2::{day2, Day2Input, Rules};
+use crate::day20::{day20, Day20Input};
+use crate::day21::{day21, Day21Input};
+use crate::day22::{day22, Day22Input};
+use crate::day23::day23;
+use crate::day24::day24;
+use crate::day25::day25;
+use crate::day3::day3;
+use crate::day4::day4;
+use crate::day5::day5;
+use crate::day6::day6;
+use crate::day7::day7;
+use crate::day8::day8;
+use crate::day9::day9;
+use crate::explain::Explanation;
+use crate::player::Animation;
+use crate::render::Render;
+use crate::{
+    day1, day10, day11, day12, day13, day14, day15, day16, day17, day18, day19, day2, day20, day21,
+    day22, day23, day24, day25, day3, day4, day5, day6, day7, day8, day9, parse,
+};
+
+pub const DAYS: std::ops::RangeInclusive<i16> = 1..=25;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Answers {
+    pub part1: String,
+    pub part2: String,
+}
+
+/// Puzzle parameters which aren't part of the input, e.g. the size of the
+/// grid. Unset values fall back to the real puzzle's.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Params {
+    pub width: Option<isize>,
+    pub height: Option<isize>,
+    pub grid_size: Option<usize>,
+    pub falls: Option<usize>,
+    pub cheat_threshold: Option<usize>,
+    pub robots: Option<usize>,
+    pub iterations: Option<usize>,
+    pub sequence_length: Option<usize>,
+    pub dampener: Option<usize>,
+    pub min_step: Option<i64>,
+    pub max_step: Option<i64>,
+    pub monotonic: Option<bool>,
+}
+
+impl Params {
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        let invalid =
+            |e: &dyn std::fmt::Display| format!("Invalid value {} for {}: {}", value, name, e);
+        match name {
+            "width" => self.width = Some(value.parse().map_err(|e| invalid(&e))?),
+            "height" => self.height = Some(value.parse().map_err(|e| invalid(&e))?),
+            "grid_size" => self.grid_size = Some(value.parse().map_err(|e| invalid(&e))?),
+            "falls" => self.falls = Some(value.parse().map_err(|e| invalid(&e))?),
+            "cheat_threshold" => {
+                self.cheat_threshold = Some(value.parse().map_err(|e| invalid(&e))?)
+            }
+            "robots" => self.robots = Some(value.parse().map_err(|e| invalid(&e))?),
+            "iterations" => self.iterations = Some(value.parse().map_err(|e| invalid(&e))?),
+            "sequence_length" => {
+                self.sequence_length = Some(value.parse().map_err(|e| invalid(&e))?)
+            }
+            "dampener" => self.dampener = Some(value.parse().map_err(|e| invalid(&e))?),
+            "min_step" => self.min_step = Some(value.parse().map_err(|e| invalid(&e))?),
+            "max_step" => self.max_step = Some(value.parse().map_err(|e| invalid(&e))?),
+            "monotonic" => self.monotonic = Some(value.parse().map_err(|e| invalid(&e))?),
+            _ => return Err(format!("Unknown parameter {}", name)),
+        }
+        Ok(())
+    }
+
+    fn day2(&self, reports: String) -> Day2Input {
+        Day2Input {
+            reports,
+            dampener: self.dampener.unwrap_or(1),
+            rules: Rules {
+                min_step: self.min_step.unwrap_or(1),
+                max_step: self.max_step.unwrap_or(3),
+                monotonic: self.monotonic.unwrap_or(true),
+            },
+        }
+    }
+
+    fn day14<'a>(&self, robots: &'a str) -> Day14Input<'a> {
+        Day14Input {
+            robots,
+            width: self.width.unwrap_or(101),
+            height: self.height.unwrap_or(103),
+        }
+    }
+
+    fn day18(&self, bytes: String) -> InputDay18 {
+        InputDay18 {
+            bytes,
+            grid_size: self.grid_size.unwrap_or(71),
+            falls: self.falls.unwrap_or(1024),
+        }
+    }
+
+    fn day20(&self, maze: String) -> Day20Input {
+        Day20Input {
+            maze,
+            cheat_threshold: self.cheat_threshold.unwrap_or(100),
+        }
+    }
+
+    fn day21(&self, codes: String) -> Day21Input {
+        Day21Input {
+            codes,
+            robots: self.robots.unwrap_or(25),
+        }
+    }
+
+    fn day22(&self, buyers: String) -> Day22Input {
+        Day22Input {
+            buyers,
+            iterations: self.iterations.unwrap_or(2000),
+            sequence_length: self.sequence_length.unwrap_or(4),
+        }
+    }
+}
+
+pub fn input_path(day: i16) -> String {
+    format!("src/day{}_input.txt", day)
+}
+
+pub fn read_input(day: i16) -> String {
+    let mut input = String::new();
+    let mut file = File::open(input_path(day)).expect("Error opening input");
+    file.read_to_string(&mut input)
+        .expect("Error reading input");
+    input
+}
+
+/// Both answers for a day, or None if the day isn't solved yet. Malformed
+/// input is an error.
+pub fn solve(day: i16, input: String, params: &Params) -> Option<Result<Answers, String>> {
+    solve_day(day, input, params).transpose()
+}
+
+fn solve_day(day: i16, input: String, params: &Params) -> Result<Option<Answers>, String> {
+    let answers = |part1: &dyn ToString, part2: &dyn ToString| Answers {
+        part1: part1.to_string(),
+        part2: part2.to_string(),
+    };
+
+    let result = match day {
+        1 => {
+            let result = day1(input)?;
+            answers(&result.diff_sum, &result.similarity_score)
+        }
+        2 => {
+            let result = day2(params.day2(input))?;
+            answers(&result.num_safe, &result.num_safe_with_dampener)
+        }
+        3 => {
+            let result = day3(input)?;
+            answers(&result.part1, &result.part2)
+        }
+        4 => {
+            let result = day4(input)?;
+            answers(&result.part1, &result.part2)
+        }
+        5 => {
+            let result = day5(input)?;
+            answers(&result.part1, &result.part2)
+        }
+        6 => {
+            let result = day6(input)?;
+            answers(&result.part1, &result.part2)
+        }
+        7 => {
+            let result = day7(input)?;
+            answers(&result.part1, &result.part2)
+        }
+        8 => {
+            let result = day8(input)?;
+            answers(&result.part1, &result.part2)
+        }
+        9 => {
+            let result = day9(input)?;
+            answers(&result.part1, &result.part2)
+        }
+        10 => {
+            let result = day10(input)?;
+            answers(&result.part1, &result.part2)
+        }
+        11 => {
+            let result = day11(input)?;
+            answers(&result.part1, &result.part2)
+        }
+        12 => {
+            let result = day12(input)?;
+            answers(&result.part1, &result.part2)
+        }
+        13 => {
+            let result = day13(input)?;
+            answers(&result.part1, &result.part2)
+        }
+        14 => {
+            let result = day14(params.day14(&input))?;
+            answers(&result.part1, &result.part2)
+        }
+        15 => {
+            let result = day15(input)?;
+            answers(&result.part1, &result.part2)
+        }
+        16 => {
+            let result = day16(input)?;
+            answers(&result.part1, &result.part2)
+        }
+        17 => {
+            let result = day17(input)?;
+            answers(&result.part1, &result.part2)
+        }
+        18 => {
+            let result = day18(params.day18(input))?;
+            answers(&result.part1, &result.part2)
+        }
+        19 => {
+            let result = day19(input)?;
+            answers(&result.part1, &result.part2)
+        }
+        20 => {
+            let result = day20(params.day20(input))?;
+            answers(&result.part1, &result.part2)
+        }
+        21 => {
+            let result = day21(params.day21(input))?;
+            answers(&result.part1, &result.part2)
+        }
+        22 => {
+            let result = day22(params.day22(input))?;
+            answers(&result.part1, &result.part2)
+        }
+        23 => {
+            let result = day23(input)?;
+            answers(&result.part1, &result.part2)
+        }
+        24 => {
+            let result = day24(input)?;
+            answers(&result.part1, &result.part2)
+        }
+        // the last day only has one puzzle
+        25 => {
+            let result = day25(input)?;
+            answers(&result.part1, &"")
+        }
+        _ => return Ok(None),
+    };
+
+    Ok(Some(result))
+}
+
+/// Solves only one part, for inputs which the other part can't handle, e.g.
+/// the day 14 example has no christmas tree.
+pub fn solve_part(
+    day: i16,
+    part: u8,
+    input: String,
+    params: &Params,
+) -> Option<Result<String, String>> {
+    solve_day_part(day, part, input, params).transpose()
+}
+
+fn solve_day_part(
+    day: i16,
+    part: u8,
+    input: String,
+    params: &Params,
+) -> Result<Option<String>, String> {
+    let answer = match (day, part) {
+        (6, 1) => day6::part1(input)?.to_string(),
+        (6, 2) => day6::part2(input)?.to_string(),
+        (7, 1) => day7::part1(input)?.to_string(),
+        (7, 2) => day7::part2(input)?.to_string(),
+        (9, 1) => day9::part1(input)?.to_string(),
+        (9, 2) => day9::part2(input)?.to_string(),
+        (10, 1) => day10::part1(input)?.to_string(),
+        (10, 2) => day10::part2(input)?.to_string(),
+        (12, 1) => day12::part1(input)?.to_string(),
+        (12, 2) => day12::part2(input)?.to_string(),
+        (13, 1) => day13::part1(input)?.to_string(),
+        (13, 2) => day13::part2(input)?.to_string(),
+        (14, 1) => day14::part1(params.day14(&input))?.to_string(),
+        (14, 2) => day14::part2(params.day14(&input))?.to_string(),
+        (15, 1) => day15::part1(input)?.to_string(),
+        (15, 2) => day15::part2(input)?.to_string(),
+        (16, 1) => day16::part1(input)?.to_string(),
+        (16, 2) => day16::part2(input)?.to_string(),
+        (17, 1) => day17::part1(input)?,
+        (17, 2) => day17::part2(input)?.to_string(),
+        (18, 1) => day18::part1(params.day18(input))?.to_string(),
+        (18, 2) => day18::part2(params.day18(input))?,
+        (19, 1) => day19::part1(input)?.to_string(),
+        (19, 2) => day19::part2(input)?.to_string(),
+        (20, 1) => day20::part1(params.day20(input))?.to_string(),
+        (20, 2) => day20::part2(params.day20(input))?.to_string(),
+        (21, 1) => day21::part1(params.day21(input))?.to_string(),
+        (21, 2) => day21::part2(params.day21(input))?.to_string(),
+        (22, 1) => day22::part1(params.day22(input))?.to_string(),
+        (22, 2) => day22::part2(params.day22(input))?.to_string(),
+        (24, 1) => day24::part1(input)?.to_string(),
+        (24, 2) => day24::part2(input)?,
+        // the remaining days solve both parts together
+        (_, 1) => return Ok(solve_day(day, input, params)?.map(|answers| answers.part1)),
+        (_, 2) => return Ok(solve_day(day, input, params)?.map(|answers| answers.part2)),
+        _ => return Ok(None),
+    };
+
+    Ok(Some(answer))
+}
+
+/// Parses a day's input without solving it, or None for days which scan
+/// their input as they go.
+pub fn parse(day: i16, input: String, params: &Params) -> Option<Result<(), String>> {
+    let parsed = match day {
+        1 => day1::parse_input(&input).map(drop),
+        2 => day2::parse_input(&input).map(drop),
+        4 => day4::parse_input(&input).map(drop),
+        5 => day5::parse_input(&input).map(drop),
+        6 => day6::init(&input).map(drop),
+        7 => day7::parse_input(&input).map(drop),
+        8 => day8::parse_input(&input).map(drop),
+        9 => day9::parse_input(&input).map(drop),
+        10 => day10::parse_input(&input).map(drop),
+        11 => day11::parse_input(&input).map(drop),
+        12 => parse::grid(&input).map(drop),
+        13 => day13::parse_machines(&input, 0.0).map(drop),
+        14 => day14::parse_robots(&params.day14(&input)).map(drop),
+        15 => day15::split_input(&input).map(drop),
+        16 => day16::parse_board(input).map(drop),
+        17 => day17::parse_input(input).map(drop),
+        18 => day18::parse_bytes(&params.day18(input)).map(drop),
+        19 => day19::parse_input(&input).map(drop),
+        20 => day20::parse_maze(input).map(drop),
+        21 => day21::parse_input(&input).map(drop),
+        22 => day22::parse_input(&input).map(drop),
+        23 => day23::parse_input(&input).map(drop),
+        24 => day24::parse_input(&input).map(drop),
+        25 => day25::parse_input(&input).map(drop),
+        _ => return None,
+    };
+
+    Some(parsed)
+}
+
+/// Derivations for the days which can explain their answers.
+pub fn explain(
+    day: i16,
+    input: String,
+    params: &Params,
+) -> Option<Result<Vec<Explanation>, String>> {
+    match day {
+        2 => Some(day2::explain(params.day2(input))),
+        3 => Some(day3::explain(input)),
+        5 => Some(day5::explain(input)),
+        7 => Some(day7::explain(input)),
+        13 => Some(day13::explain(input)),
+        _ => None,
+    }
+}
+
+/// A readable view of a day's parsed input
+pub fn describe(day: i16, input: String) -> Option<Result<String, String>> {
+    match day {
+        5 => Some(day5::describe(input)),
+        17 => Some(day17::describe(input)),
+        19 => Some(day19::describe(input)),
+        _ => None,
+    }
+}
+
+/// The state which a day can draw, e.g. the robots forming a christmas tree
+pub fn render_state(
+    day: i16,
+    input: String,
+    params: &Params,
+) -> Option<Result<Box<dyn Render>, String>> {
+    let state = match day {
+        14 => day14::render_state(params.day14(&input)).map(|s| Box::new(s) as Box<dyn Render>),
+        15 => day15::render_state(input).map(|s| Box::new(s) as Box<dyn Render>),
+        16 => day16::render_state(input).map(|s| Box::new(s) as Box<dyn Render>),
+        _ => return None,
+    };
+
+    Some(state)
+}
+
+/// The step-by-step simulation of a day, for playing in the terminal
+pub fn animation(
+    day: i16,
+    input: &str,
+    params: &Params,
+) -> Option<Result<Box<dyn Animation>, String>> {
+    fn boxed(animation: impl Animation + 'static) -> Box<dyn Animation> {
+        Box::new(animation)
+    }
+
+    let animation = match day {
+        6 => day6::animation(input.to_owned()).map(boxed),
+        14 => day14::animation(params.day14(input)).map(boxed),
+        15 => day15::animation(input.to_owned()).map(boxed),
+        18 => day18::animation(params.day18(input.to_owned())).map(boxed),
+        _ => return None,
+    };
+
+    Some(animation)
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "solver panicked".to_owned()
+    }
+}
+
+/// Stops panics from printing, for modes which report them as errors instead
+pub fn quiet_panics() {
+    panic::set_hook(Box::new(|_| {}));
+}
+
+/// Runs `f`, turning a panic into an error message
+pub fn catching<T>(f: impl FnOnce() -> T) -> Result<T, String> {
+    panic::catch_unwind(panic::AssertUnwindSafe(f)).map_err(panic_message)
+}
+
+/// Like `solve`, but a panicking solver is reported as an error rather than
+/// taking the whole process down.
+pub fn solve_catching(day: i16, input: String, params: &Params) -> Result<Answers, String> {
+    catching(|| solve(day, input, params))?
+        .ok_or_else(|| format!("Unknown or unfinished day {}", day))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solves_known_day() {
+        let input = r"3   4
+4   3
+2   5
+1   3
+3   9
+3   3
+";
+        let result = solve(1, input.to_owned(), &Params::default());
+        assert_eq!(
+            result,
+            Some(Ok(Answers {
+                part1: "11".to_owned(),
+                part2: "31".to_owned(),
+            }))
+        );
+    }
+
+    #[test]
+    fn reports_solver_panics() {
+        let result = solve_catching(1, "not a list".to_owned(), &Params::default());
+        assert!(result.is_err());
+
+        let result = solve_catching(99, "".to_owned(), &Params::default());
+        assert_eq!(result, Err("Unknown or unfinished day 99".to_owned()));
+    }
+
+    #[test]
+    fn reports_malformed_input() {
+        let result = parse(17, "Program: 0,1,5".to_owned(), &Params::default());
+        assert_eq!(result, Some(Err("No \"Register A:\" line".to_owned())));
+
+        let result = solve(5, "47|53\n\n75,47|53".to_owned(), &Params::default());
+        assert!(matches!(result, Some(Err(_))));
+        assert_eq!(parse(3, "".to_owned(), &Params::default()), None);
+    }
+
+    #[test]
+    fn sets_params() {
+        let mut params = Params::default();
+        params.set("width", "11").unwrap();
+        params.set("falls", "12").unwrap();
+        assert_eq!(params.width, Some(11));
+        assert_eq!(params.falls, Some(12));
+        assert!(params.set("falls", "-1").is_err());
+        params.set("robots", "3").unwrap();
+        assert_eq!(params.robots, Some(3));
+        assert!(params.set("speed", "1").is_err());
+    }
+}
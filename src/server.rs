@@ -0,0 +1,408 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::runner::{solve_catching, Answers, Params, DAYS};
+
+const MAX_HEADER_LINES: usize = 100;
+const MAX_LINE_BYTES: u64 = 8 * 1024;
+const SOCKET_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_CONNECTIONS: usize = 64;
+
+#[derive(Clone, Copy, Debug)]
+pub struct ServerConfig {
+    pub port: u16,
+    pub max_body_bytes: usize,
+    pub solve_timeout: Duration,
+    pub max_solves: usize,
+}
+
+#[derive(Debug, PartialEq)]
+struct Request {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    body: String,
+}
+
+#[derive(Debug, PartialEq)]
+struct Response {
+    status: u16,
+    body: String,
+}
+
+impl Response {
+    fn json(status: u16, body: String) -> Self {
+        Response { status, body }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Response::json(status, format!("{{\"error\":{}}}", json_string(message)))
+    }
+}
+
+fn status_text(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        411 => "Length Required",
+        413 => "Payload Too Large",
+        422 => "Unprocessable Entity",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        _ => "Internal Server Error",
+    }
+}
+
+/// A limited number of things allowed to run at once
+#[derive(Clone)]
+struct Slots {
+    taken: Arc<Mutex<usize>>,
+    limit: usize,
+}
+
+/// A taken slot, given back when dropped
+struct Slot(Arc<Mutex<usize>>);
+
+impl Slots {
+    fn new(limit: usize) -> Self {
+        Slots {
+            taken: Arc::new(Mutex::new(0)),
+            limit,
+        }
+    }
+
+    fn try_take(&self) -> Option<Slot> {
+        let mut taken = self.taken.lock().unwrap();
+        if *taken >= self.limit {
+            return None;
+        }
+        *taken += 1;
+        Some(Slot(self.taken.clone()))
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        *self.0.lock().unwrap() -= 1;
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let escaped = value
+        .chars()
+        .map(|ch| match ch {
+            '"' => "\\\"".to_owned(),
+            '\\' => "\\\\".to_owned(),
+            '\n' => "\\n".to_owned(),
+            '\r' => "\\r".to_owned(),
+            '\t' => "\\t".to_owned(),
+            c if (c as u32) < 0x20 => format!("\\u{:04x}", c as u32),
+            c => c.to_string(),
+        })
+        .collect::<String>();
+    format!("\"{}\"", escaped)
+}
+
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.split_once('=') {
+            Some((key, value)) => (key.to_owned(), value.to_owned()),
+            None => (pair.to_owned(), "".to_owned()),
+        })
+        .collect()
+}
+
+/// Reads a request, rejecting bodies over the limit before reading them
+fn read_request<R: BufRead>(reader: &mut R, max_body_bytes: usize) -> Result<Request, Response> {
+    let bad_request = |message: &str| Response::error(400, message);
+
+    let mut request_line = String::new();
+    reader
+        .take(MAX_LINE_BYTES)
+        .read_line(&mut request_line)
+        .map_err(|_| bad_request("Could not read request"))?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(bad_request("Malformed request line"));
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    let mut content_length = None;
+    for _ in 0..MAX_HEADER_LINES {
+        let mut line = String::new();
+        reader
+            .take(MAX_LINE_BYTES)
+            .read_line(&mut line)
+            .map_err(|_| bad_request("Could not read headers"))?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = Some(
+                    str::parse::<usize>(value.trim())
+                        .map_err(|_| bad_request("Invalid Content-Length"))?,
+                );
+            }
+        }
+    }
+
+    let body = match (method, content_length) {
+        (_, Some(length)) if length > max_body_bytes => {
+            return Err(Response::error(
+                413,
+                &format!("Body is larger than {} bytes", max_body_bytes),
+            ))
+        }
+        (_, Some(length)) => {
+            let mut body = vec![0; length];
+            reader
+                .read_exact(&mut body)
+                .map_err(|_| bad_request("Body shorter than Content-Length"))?;
+            String::from_utf8(body).map_err(|_| bad_request("Body is not UTF-8"))?
+        }
+        ("POST", None) => return Err(Response::error(411, "Content-Length is required")),
+        (_, None) => String::new(),
+    };
+
+    Ok(Request {
+        method: method.to_owned(),
+        path: path.to_owned(),
+        query: parse_query(query),
+        body,
+    })
+}
+
+/// Runs `solve` on its own thread if a solver slot is free. The solver can't
+/// be cancelled, so a timed-out one keeps its slot until it finishes, which
+/// stops slow requests piling up solvers.
+fn solve_with_timeout(
+    day: i16,
+    solve: impl FnOnce() -> Result<Answers, String> + Send + 'static,
+    timeout: Duration,
+    solvers: &Slots,
+) -> Response {
+    let Some(slot) = solvers.try_take() else {
+        return Response::error(503, "Too many solvers running, try again later");
+    };
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let start = Instant::now();
+        let result = solve();
+        let _ = sender.send((result, start.elapsed()));
+        drop(slot);
+    });
+
+    match receiver.recv_timeout(timeout) {
+        Ok((Ok(answers), elapsed)) => Response::json(
+            200,
+            format!(
+                "{{\"day\":{},\"part1\":{},\"part2\":{},\"elapsed_ms\":{:.3}}}",
+                day,
+                json_string(&answers.part1),
+                json_string(&answers.part2),
+                elapsed.as_secs_f64() * 1000.0
+            ),
+        ),
+        Ok((Err(e), _)) => Response::error(422, &e),
+        Err(_) => Response::error(
+            504,
+            &format!("Solver took longer than {}s", timeout.as_secs_f64()),
+        ),
+    }
+}
+
+fn route(request: Request, config: &ServerConfig, solvers: &Slots) -> Response {
+    let segments = request
+        .path
+        .trim_matches('/')
+        .split('/')
+        .collect::<Vec<&str>>();
+
+    match segments.as_slice() {
+        ["days"] => {
+            if request.method != "GET" {
+                return Response::error(405, "Use GET");
+            }
+            let days = DAYS.map(|d| d.to_string()).collect::<Vec<String>>();
+            Response::json(200, format!("{{\"days\":[{}]}}", days.join(",")))
+        }
+        ["days", day, "solve"] => {
+            if request.method != "POST" {
+                return Response::error(405, "Use POST with the input as the body");
+            }
+            let Some(day) = str::parse::<i16>(day).ok().filter(|d| DAYS.contains(d)) else {
+                return Response::error(404, &format!("Unknown or unfinished day {}", day));
+            };
+            let mut params = Params::default();
+            for (name, value) in request.query.iter() {
+                if let Err(e) = params.set(name, value) {
+                    return Response::error(400, &e);
+                }
+            }
+            let input = request.body;
+            let solve = move || solve_catching(day, input, &params);
+            solve_with_timeout(day, solve, config.solve_timeout, solvers)
+        }
+        _ => Response::error(404, "Not found"),
+    }
+}
+
+fn respond(mut stream: TcpStream, response: Response) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        status_text(response.status),
+        response.body.len(),
+        response.body
+    )?;
+    stream.flush()
+}
+
+fn handle(stream: TcpStream, config: &ServerConfig, solvers: &Slots) -> io::Result<()> {
+    stream.set_read_timeout(Some(SOCKET_TIMEOUT))?;
+    stream.set_write_timeout(Some(SOCKET_TIMEOUT))?;
+
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let response = match read_request(&mut reader, config.max_body_bytes) {
+        Ok(request) => route(request, config, solvers),
+        Err(response) => response,
+    };
+    respond(stream, response)
+}
+
+pub fn serve(config: ServerConfig) -> io::Result<()> {
+    let listener = TcpListener::bind(("127.0.0.1", config.port))?;
+    println!("Listening on http://{}", listener.local_addr()?);
+    let connections = Slots::new(MAX_CONNECTIONS);
+    let solvers = Slots::new(config.max_solves);
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let Some(connection) = connections.try_take() else {
+                    let _ = stream.set_write_timeout(Some(SOCKET_TIMEOUT));
+                    let busy = Response::error(503, "Too many connections, try again later");
+                    if let Err(e) = respond(stream, busy) {
+                        eprintln!("Error handling request: {}", e);
+                    }
+                    continue;
+                };
+                let solvers = solvers.clone();
+                thread::spawn(move || {
+                    if let Err(e) = handle(stream, &config, &solvers) {
+                        eprintln!("Error handling request: {}", e);
+                    }
+                    drop(connection);
+                });
+            }
+            Err(e) => eprintln!("Error accepting connection: {}", e),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: ServerConfig = ServerConfig {
+        port: 0,
+        max_body_bytes: 64,
+        solve_timeout: Duration::from_secs(5),
+        max_solves: 2,
+    };
+
+    fn request(raw: &str) -> Response {
+        match read_request(&mut raw.as_bytes(), CONFIG.max_body_bytes) {
+            Ok(request) => route(request, &CONFIG, &Slots::new(CONFIG.max_solves)),
+            Err(response) => response,
+        }
+    }
+
+    #[test]
+    fn solves_posted_input() {
+        let body = "p=0,4 v=3,-3\np=6,3 v=-1,-3\np=10,3 v=-1,2\n";
+        let response = request(&format!(
+            "POST /days/1/solve HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}",
+            24, "3   4\n4   3\n2   5\n1   3\n"
+        ));
+        assert_eq!(response.status, 200);
+        assert!(response
+            .body
+            .starts_with("{\"day\":1,\"part1\":\"5\",\"part2\":\"10\",\"elapsed_ms\":"));
+
+        let response = request(&format!(
+            "POST /days/14/solve?width=x HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        ));
+        assert_eq!(response.status, 400);
+    }
+
+    #[test]
+    fn rejects_bad_requests() {
+        assert_eq!(request("GET /days HTTP/1.1\r\n\r\n").status, 200);
+        assert_eq!(request("GET /days/1/solve HTTP/1.1\r\n\r\n").status, 405);
+        assert_eq!(
+            request("POST /days/99/solve HTTP/1.1\r\nContent-Length: 0\r\n\r\n").status,
+            404
+        );
+        assert_eq!(request("POST /days/1/solve HTTP/1.1\r\n\r\n").status, 411);
+        assert_eq!(
+            request("POST /days/1/solve HTTP/1.1\r\nContent-Length: 65\r\n\r\n").status,
+            413
+        );
+        assert_eq!(
+            request("POST /days/1/solve HTTP/1.1\r\nContent-Length: 5\r\n\r\nnope!").status,
+            422
+        );
+    }
+
+    /// A solver which runs until `finish` is dropped
+    fn blocked_solver() -> (
+        impl FnOnce() -> Result<Answers, String> + Send + 'static,
+        mpsc::Sender<()>,
+    ) {
+        let (finish, finished) = mpsc::channel::<()>();
+        let solve = move || {
+            let _ = finished.recv();
+            Err("Finished".to_owned())
+        };
+        (solve, finish)
+    }
+
+    #[test]
+    fn times_out_slow_solvers() {
+        let solvers = Slots::new(1);
+        let (solve, finish) = blocked_solver();
+        let response = solve_with_timeout(1, solve, Duration::from_millis(10), &solvers);
+        assert_eq!(response.status, 504);
+
+        // the timed-out solver still holds the only slot until it finishes
+        let (solve, _finish) = blocked_solver();
+        let response = solve_with_timeout(1, solve, Duration::from_millis(10), &solvers);
+        assert_eq!(response.status, 503);
+
+        drop(finish);
+        while solvers.try_take().is_none() {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn escapes_json() {
+        assert_eq!(json_string("a\"b\\c\nd"), "\"a\\\"b\\\\c\\nd\"");
+    }
+}
@@ -0,0 +1,66 @@
+//! Runs every registered day against its fixtures in `fixtures/day<N>/`.
+
+use advent_of_code_2024::fixtures::{read_fixtures, Fixture};
+use advent_of_code_2024::runner::{catching, quiet_panics, solve_part, DAYS};
+
+/// The differences between a fixture's expected and actual answers
+fn check_fixture(day: i16, fixture: &Fixture) -> Vec<String> {
+    if fixture.part1.is_none() && fixture.part2.is_none() {
+        return vec!["no answers to check".to_owned()];
+    }
+
+    [(1, &fixture.part1), (2, &fixture.part2)]
+        .into_iter()
+        .filter_map(|(part, expected)| {
+            let expected = expected.as_ref()?;
+            match catching(|| solve_part(day, part, fixture.input.clone(), &fixture.params)) {
+                Ok(Some(Ok(actual))) if actual == *expected => None,
+                Ok(Some(Ok(actual))) => Some(format!(
+                    "part {}: expected {}, got {}",
+                    part, expected, actual
+                )),
+                Ok(Some(Err(e))) => Some(format!("part {}: error: {}", part, e)),
+                Ok(None) => Some(format!("part {}: day is not registered", part)),
+                Err(e) => Some(format!("part {}: panicked: {}", part, e)),
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn solves_every_fixture() {
+    quiet_panics();
+
+    let mut failures = vec![];
+    let mut checked = 0;
+    for day in DAYS {
+        let fixtures = match read_fixtures(day) {
+            Ok(fixtures) => fixtures,
+            Err(e) => {
+                failures.push(format!("day{}/{}", day, e));
+                continue;
+            }
+        };
+        if fixtures.is_empty() {
+            failures.push(format!("day{}: no fixtures", day));
+        }
+        for fixture in fixtures {
+            checked += 1;
+            failures.extend(
+                check_fixture(day, &fixture)
+                    .into_iter()
+                    .map(|diff| format!("day{}/{}: {}", day, fixture.name, diff)),
+            );
+        }
+    }
+
+    // bring the default hook back so the failure below is reported
+    drop(std::panic::take_hook());
+    assert!(
+        failures.is_empty(),
+        "{} differences across {} fixtures:\n{}",
+        failures.len(),
+        checked,
+        failures.join("\n")
+    );
+}
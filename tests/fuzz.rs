@@ -0,0 +1,31 @@
+//! A short fuzzing run over every day, so a parser which panics on
+//! malformed input is caught before it ships. `cargo run -- fuzz` runs longer.
+
+use std::time::Duration;
+
+use advent_of_code_2024::fuzz::{fuzz_day, FuzzConfig};
+use advent_of_code_2024::runner::{quiet_panics, DAYS};
+
+#[test]
+fn survives_mutated_examples() {
+    quiet_panics();
+
+    let config = FuzzConfig {
+        iterations: 100,
+        seed: 1,
+        timeout: Duration::from_secs(10),
+    };
+    let findings = DAYS
+        .flat_map(|day| fuzz_day(day, &config).unwrap())
+        .map(|finding| finding.to_string())
+        .collect::<Vec<String>>();
+
+    // bring the default hook back so the failure below is reported
+    drop(std::panic::take_hook());
+    assert!(
+        findings.is_empty(),
+        "{} findings:\n{}",
+        findings.len(),
+        findings.join("\n")
+    );
+}